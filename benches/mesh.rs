@@ -0,0 +1,36 @@
+use criterion::{
+    criterion_group,
+    criterion_main,
+    BenchmarkId,
+    Criterion
+};
+
+use solarsim::{
+    sim::{Sim, SimConfig},
+    ui
+};
+
+fn build_mesh(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ui::build_mesh");
+    for ship_count in [1000, 10000] {
+        let config = SimConfig::default()
+            .with_seed(1)
+            .with_system_rad(4f32)
+            .with_miner_count(ship_count);
+
+        let mut sim = Sim::new(config);
+        for _ in 0..100 {
+            sim.update();
+        }
+
+        let theme = ui::Theme::default();
+        group.bench_with_input(BenchmarkId::from_parameter(ship_count), &sim, |b, sim| {
+            b.iter(|| ui::build_mesh(sim, &theme))
+        } );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, build_mesh);
+criterion_main!(benches);
@@ -0,0 +1,56 @@
+use criterion::{
+    criterion_group,
+    criterion_main,
+    BatchSize,
+    BenchmarkId,
+    Criterion
+};
+
+use solarsim::sim::{Sim, SimConfig};
+
+fn generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Sim::new");
+    for system_rad in [2f32, 4f32, 8f32] {
+        let config = SimConfig::default()
+            .with_seed(1)
+            .with_system_rad(system_rad);
+
+        group.bench_with_input(BenchmarkId::from_parameter(system_rad), &config, |b, config| {
+            b.iter(|| Sim::new(config.clone()))
+        } );
+    }
+
+    group.finish();
+}
+
+fn update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Sim::update");
+    for ship_count in [100, 1000, 10000] {
+        let config = SimConfig::default()
+            .with_seed(1)
+            .with_miner_count(ship_count);
+
+        // Let the fleet settle into its usual routine first
+        let mut sim = Sim::new(config);
+        for _ in 0..100 {
+            sim.update();
+        }
+
+        // Every iteration starts from that same tick, so later ones don't time a drifting sim
+        // The sim is returned so that dropping it isn't timed either
+        group.bench_function(BenchmarkId::from_parameter(ship_count), |b| {
+            b.iter_batched(
+                || sim.clone(),
+                |mut sim| {
+                    sim.update();
+                    sim
+                },
+                BatchSize::LargeInput)
+        } );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, generation, update);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+//! Procedurally generated solar systems, populated by miners, traders and pirates
+//!
+//! The simulation in `sim` has no graphics dependencies.
+//! The renderer in `ui` is only built with the default `ui` feature.
+
+pub mod sim;
+
+#[cfg(feature = "ui")]
+pub mod ui;
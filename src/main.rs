@@ -1,10 +1,144 @@
-mod sim;
-mod ui;
+use std::path::Path;
+
+use solarsim::{sim, ui};
 
 fn main() {
-    let sim = sim::Sim::default();
-    
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("render-frame") {
+        args.next();
+        render_frame(args);
+        return;
+    }
+
+    let mut config = ui::UiConfig::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--msaa" => {
+                let samples = args.next()
+                    .and_then(|samples| samples.parse().ok())
+                    .filter(|samples| matches!(samples, 1 | 4 | 8))
+                    .unwrap_or_else(|| usage());
+
+                config = config.with_msaa_samples(samples);
+            },
+            "--theme" => {
+                config = config.with_theme(theme(args.next()));
+            },
+            "--screensaver" => {
+                config = config.with_screensaver(true);
+            },
+            _ => usage()
+        }
+    }
+
+    // The viewer loads ./scripts itself, SimConfig::default doesn't load any
+    let system_config = sim::SimConfig::default().with_scripts(Path::new("scripts"));
+    let galaxy = sim::galaxy::Galaxy::new(
+        sim::galaxy::GalaxyConfig::default().with_system_config(system_config)
+    );
+
     pollster::block_on(
-        ui::run(sim)
+        ui::run(galaxy, config)
     );
 }
+
+// Largest width or height render-frame accepts, the image is drawn at twice that in memory
+const MAX_SIZE: u32 = 4096;
+
+/// Runs a galaxy for a number of ticks, then draws one of its systems to a PNG,
+/// or an SVG if that's the output's extension
+/// Everything is drawn on the CPU, so it works on machines without a GPU
+fn render_frame(mut args: impl Iterator<Item = String>) {
+    let mut galaxy_config = sim::galaxy::GalaxyConfig::default();
+    let mut ticks = 0;
+    let mut system = 0;
+    let (mut width, mut height) = (1280, 720);
+    let mut theme = ui::Theme::default();
+    let (mut orbits, mut labels) = (false, false);
+    let mut out = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => galaxy_config = galaxy_config.with_seed(parse(args.next())),
+            "--tick" => ticks = parse(args.next()),
+            "--system" => system = parse(args.next()),
+            "--size" => {
+                (width, height) = args.next()
+                    .and_then(|size| {
+                        let (width, height) = size.split_once('x')?;
+                        Some((width.parse().ok()?, height.parse().ok()?))
+                    } )
+                    .filter(|&(width, height)| (1..=MAX_SIZE).contains(&width) && (1..=MAX_SIZE).contains(&height))
+                    .unwrap_or_else(|| usage());
+            },
+            "--theme" => theme = self::theme(args.next()),
+            "--scripts" => {
+                let dir = args.next().unwrap_or_else(|| usage());
+                let system_config = sim::SimConfig::default().with_scripts(Path::new(&dir));
+                galaxy_config = galaxy_config.with_system_config(system_config);
+            },
+            "--orbits" => orbits = true,
+            "--labels" => labels = true,
+            _ if out.is_none() && !arg.starts_with('-') => out = Some(arg),
+            _ => usage()
+        }
+    }
+
+    let out = out.unwrap_or_else(|| usage());
+
+    let mut galaxy = sim::galaxy::Galaxy::new(galaxy_config);
+    for _ in 0..ticks {
+        galaxy.update();
+    }
+
+    let sim = galaxy.systems.get(system).unwrap_or_else(|| {
+        eprintln!("system {} doesn't exist, there are {}", system, galaxy.systems.len());
+        std::process::exit(2)
+    } );
+
+    let written = std::fs::File::create(&out).and_then(|file| {
+        use std::io::Write;
+
+        let mut file = std::io::BufWriter::new(file);
+        if out.ends_with(".svg") {
+            let config = ui::SvgConfig::default()
+                .with_orbits(orbits)
+                .with_labels(labels)
+                .with_size(width.max(height));
+
+            ui::write_svg(sim, &theme, &config, &mut file)?;
+        } else {
+            // Framed like the start of an interactive session
+            let camera = ui::Camera::new((0f32, 0f32).into(), 1f32, width as f32 / height as f32);
+            ui::rasterize(&ui::build_mesh(sim, &theme), &camera, width, height).write_png(&mut file)?;
+        }
+
+        file.flush()
+    } );
+
+    if let Err(e) = written {
+        eprintln!("{}: {}", out, e);
+        std::process::exit(1);
+    }
+}
+
+/// Either a built-in theme, or the path to a theme file
+fn theme(name: Option<String>) -> ui::Theme {
+    let name = name.unwrap_or_else(|| usage());
+    ui::Theme::builtin(&name).unwrap_or_else(|| {
+        ui::Theme::load(Path::new(&name)).unwrap_or_else(|e| {
+            eprintln!("{}: {}", name, e);
+            std::process::exit(2)
+        } )
+    } )
+}
+
+fn parse<T: std::str::FromStr>(arg: Option<String>) -> T {
+    arg.and_then(|arg| arg.parse().ok()).unwrap_or_else(|| usage())
+}
+
+fn usage() -> ! {
+    eprintln!("usage: solarsim [--msaa 1|4|8] [--theme default|hashed|okabe-ito|tol|FILE] [--screensaver]");
+    eprintln!("       solarsim render-frame [--seed S] [--tick N] [--system I] [--size WxH] [--theme THEME] [--scripts DIR] [--orbits] [--labels] OUT.png|OUT.svg");
+    std::process::exit(2)
+}
@@ -0,0 +1,171 @@
+use std::cmp::Ordering::Equal;
+
+use rand::{
+    seq::IteratorRandom,
+    rngs::SmallRng
+};
+
+use cgmath::{
+    Point2,
+    MetricSpace
+};
+
+use crate::sim::{
+    Sim,
+    ship::{Ship, ShipGoal, ShipIntent, Cargo, JobId},
+    planet::PlanetFeature
+};
+
+use super::{
+    ShipBehavior,
+    Trail,
+    stock
+};
+
+/// Harvests ore and ice, then deposits it at the nearest station
+pub struct Miner;
+
+impl ShipBehavior for Miner {
+    fn name(&self) -> &'static str { "miner" }
+
+    fn populate(&self, sim: &mut Sim, job: JobId) {
+        // Ships start at random points, with random destinations
+        let ores = sim.ore_indices();
+        for _ in 0..sim.config.miner_count {
+            let mut ship = Ship::new(job, sim.config.ship_speed(), &mut sim.prng);
+            // Use polar coordinates to ensure even distribution
+            ship.pos = crate::sim::rand_pos(&mut sim.prng, sim.system_rad);
+            ship.goal = ShipGoal::Visit {
+                target: *ores.iter().choose(&mut sim.prng).unwrap()
+            };
+
+            sim.ships.push(ship);
+        }
+    }
+
+    fn update(&self, sim: &Sim, ship_index: usize, rng: &mut SmallRng) -> ShipIntent {
+        // Give up the chase if a comet leaves the inner system
+        if let ShipGoal::Visit { target } = sim.ships[ship_index].goal {
+            if !sim.in_inner_system(target) {
+                let mut ship = sim.ships[ship_index].clone();
+                let target = best_deposit(sim, ship.pos);
+                ship.goal = ShipGoal::Visit { target };
+                return ShipIntent::new(ship, false);
+            }
+        }
+
+        sim.follow_goal(ship_index, rng)
+    }
+
+    fn next_goal(&self, sim: &mut Sim, ship_index: usize) -> ShipGoal {
+        match sim.ships[ship_index].goal {
+            // After arriving at station or mining site
+            ShipGoal::Visit { target } => {
+                let ship_pos = sim.ships[ship_index].pos;
+
+                // Behavior depends on the type of planet is just visited
+                match sim.system[target].feat.as_ref().unwrap() {
+                    PlanetFeature::Station { .. } => {
+                        // Deposit ore at the station
+                        // Volatiles are rare, so they are worth more
+                        *stock(&mut sim.system[target]) += match sim.ships[ship_index].cargo {
+                            Cargo::Ice => sim.config.ice_value(),
+                            Cargo::Ore => 1,
+                            Cargo::Empty => 0
+                        };
+
+                        sim.ships[ship_index].cargo = Cargo::Empty;
+
+                        // Visit another planet with ore
+                        ShipGoal::Visit { target: best_deposit(sim, ship_pos) }
+                    },
+                    PlanetFeature::Gate { .. } => {
+                        // Miners stay in their own system
+                        ShipGoal::Visit { target: best_deposit(sim, ship_pos) }
+                    },
+                    PlanetFeature::Ice if !sim.in_inner_system(target) => {
+                        // The comet got away before the miner arrived
+                        ShipGoal::Visit { target: best_deposit(sim, ship_pos) }
+                    },
+                    PlanetFeature::Ice => {
+                        // Volatiles are harvested like ordinary ore
+                        let progress = sim.config.harvest_variance();
+                        let progress = progress.choose(&mut sim.prng);
+                        ShipGoal::Wait { target, progress: progress.unwrap() }
+                    },
+                    &PlanetFeature::Ore { richness } => {
+                        // Pause to mine
+                        let progress = sim.config.harvest_variance();
+                        let progress = progress.choose(&mut sim.prng);
+                        let progress = progress.unwrap();
+
+                        // Poor deposits take longer to harvest
+                        let duration = sim.config.harvest_duration() as f32;
+                        let extra = duration / richness - duration;
+                        ShipGoal::Wait { target, progress: progress - extra as isize }
+                    }
+                }
+            },
+
+            ShipGoal::Wait { target, .. } => {
+                // Remember whether the hold is full of ore or ice
+                sim.ships[ship_index].cargo = match sim.system[target].feat {
+                    Some(PlanetFeature::Ice) => Cargo::Ice,
+                    _ => Cargo::Ore
+                };
+
+                // After mining, the ship needs to deposit
+                let stations = sim.nearest_with_feature(
+                    Some(PlanetFeature::Station { stock: 0 } ),
+                    sim.ships[ship_index].pos);
+                ShipGoal::Visit { target: stations[0] }
+            },
+
+            goal => goal
+        }
+    }
+
+    fn allows_goal(&self, sim: &Sim, _ship_index: usize, goal: ShipGoal) -> bool {
+        let feat = |pl_index: usize| {
+            sim.system.get(pl_index).and_then(|pl| pl.feat.as_ref())
+        };
+
+        match goal {
+            ShipGoal::Visit { target } => feat(target).is_some(),
+            ShipGoal::Wait { target, .. } => matches!(
+                feat(target),
+                Some(PlanetFeature::Ore { .. } | PlanetFeature::Ice)
+            ),
+            _ => false
+        }
+    }
+
+    fn color(&self, _ship: &Ship) -> [f32; 3] {
+        [1f32, 0.2f32, 0.8f32]
+    }
+
+    // Miners shuttle back and forth, so their trails are kept short
+    fn trail(&self) -> Trail { Trail { length: 40, opacity: 0.3f32 } }
+}
+
+/// Ore or ice deposit that is most worth a miner's trip
+/// Rich deposits are worth a longer trip,
+/// comets are only considered while they're in the inner system
+fn best_deposit(sim: &Sim, pos: Point2<f32>) -> usize {
+    let cost = |&pl_index: &usize| {
+        let dist = pos.distance(sim.system[pl_index].pos);
+        match sim.system[pl_index].feat {
+            Some(PlanetFeature::Ore { richness }) => dist / richness,
+            Some(PlanetFeature::Ice) if sim.in_inner_system(pl_index) => {
+                dist / sim.config.ice_value() as f32
+            },
+            _ => f32::MAX
+        }
+    };
+
+    let mut deposits = sim.ore_indices();
+    deposits.append(&mut sim.planets_with_feature(Some(PlanetFeature::Ice)));
+    deposits.into_iter()
+        .min_by(|a, b| cost(a).partial_cmp(&cost(b)).unwrap_or(Equal))
+        .unwrap()
+}
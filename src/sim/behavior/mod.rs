@@ -0,0 +1,94 @@
+mod trader;
+mod miner;
+mod pirate;
+
+pub use trader::Trader;
+pub use miner::Miner;
+pub use pirate::Pirate;
+
+use std::sync::Arc;
+
+use rand::rngs::SmallRng;
+
+use super::{
+    Sim,
+    ship::{Ship, ShipGoal, ShipIntent, JobId},
+    planet::{Planet, PlanetFeature}
+};
+
+/// Everything that makes one kind of ship behave differently from another
+/// Implement this and add it to a SimConfig to introduce a new job
+pub trait ShipBehavior: Send + Sync {
+    /// Identifies the job in behavior scripts and the UI
+    fn name(&self) -> &'static str;
+
+    /// Adds this job's initial ships to a newly generated system
+    fn populate(&self, _sim: &mut Sim, _job: JobId) {  }
+
+    /// Moves the ship for a single tick
+    /// Ships are updated in parallel, so this only sees a snapshot of the sim
+    /// Randomness must come from the given rng to keep runs reproducible
+    fn update(&self, sim: &Sim, ship_index: usize, rng: &mut SmallRng) -> ShipIntent {
+        sim.follow_goal(ship_index, rng)
+    }
+
+    /// Picks the ship's next goal after it achieved the last one
+    fn next_goal(&self, sim: &mut Sim, ship_index: usize) -> ShipGoal;
+
+    /// Whether the ship would be able to follow the given goal
+    /// Goals returned by behavior scripts are checked against this
+    fn allows_goal(&self, sim: &Sim, ship_index: usize, goal: ShipGoal) -> bool;
+
+    /// Whether pirates will raid ships of this job while they carry cargo
+    fn raidable(&self) -> bool { false }
+
+    /// Render hint, the color of the ship's hull
+    fn color(&self, ship: &Ship) -> [f32; 3];
+
+    /// Render hint, how much of the ship's recent path is drawn behind it
+    fn trail(&self) -> Trail { Trail { length: 30, opacity: 0.4 } }
+}
+
+/// A fading line behind a ship
+/// `length` is measured in ticks, `opacity` is that of its newest end
+#[derive(Copy, Clone)]
+pub struct Trail {
+    pub length: usize,
+    pub opacity: f32
+}
+
+/// Every job known to a sim, indexed by JobId
+#[derive(Clone, Default)]
+pub struct ShipRegistry {
+    behaviors: Vec<Arc<dyn ShipBehavior>>
+}
+
+impl ShipRegistry {
+    pub fn register(&mut self, behavior: impl ShipBehavior + 'static) -> JobId {
+        self.behaviors.push(Arc::new(behavior));
+        JobId(self.behaviors.len() - 1)
+    }
+
+    pub fn get(&self, job: JobId) -> &dyn ShipBehavior {
+        self.behaviors[job.0].as_ref()
+    }
+
+    pub fn jobs(&self) -> impl Iterator<Item = JobId> {
+        (0..self.behaviors.len()).map(JobId)
+    }
+
+    /// The job registered under the given ShipBehavior::name, if any
+    pub fn find(&self, name: &str) -> Option<JobId> {
+        self.jobs().find(|&job| self.behaviors[job.0].name() == name)
+    }
+}
+
+// Returns a mutable reference to the `stock` field of a station
+// Panics if given planet doesn't have a station
+fn stock(pl: &mut Planet) -> &mut usize {
+    if let Some(PlanetFeature::Station { ref mut stock } ) = pl.feat {
+        return stock;
+    }
+
+    panic!()
+}
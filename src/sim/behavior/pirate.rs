@@ -0,0 +1,141 @@
+use rand::seq::IteratorRandom;
+
+use cgmath::{
+    Point2,
+    MetricSpace
+};
+
+use crate::sim::{
+    Sim,
+    ship::{Ship, ShipGoal, Cargo, JobId}
+};
+
+use super::{
+    ShipBehavior,
+    Trail
+};
+
+/// Patrols its territory, raiding any traders carrying cargo
+pub struct Pirate;
+
+impl ShipBehavior for Pirate {
+    fn name(&self) -> &'static str { "pirate" }
+
+    fn populate(&self, sim: &mut Sim, job: JobId) {
+        // Generate a few pirate ships to steal from traders
+        for _ in 0..sim.config.pirate_count {
+            let pirate_pos = crate::sim::rand_pos(&mut sim.prng, sim.system_rad * 0.5);
+            let mut pirate = Ship::new(job, sim.config.ship_speed(), &mut sim.prng);
+            pirate.origin = pirate_pos;
+            pirate.pos = pirate_pos;
+            pirate.goal = ShipGoal::Wander; // pirates start by wandering
+
+            // Move the pirate to a random spot within its territory
+            let offset = crate::sim::rand_pos(&mut sim.prng, sim.config.pirate_territory());
+            pirate.pos.x += offset.x;
+            pirate.pos.y += offset.y;
+
+            // Add pirate after giving it a random pos
+            sim.ships.push(pirate);
+        }
+    }
+
+    fn next_goal(&self, sim: &mut Sim, ship_index: usize) -> ShipGoal {
+        match sim.ships[ship_index].goal {
+            ShipGoal::Wander => ShipGoal::Scan,
+
+            ShipGoal::Scan => {
+                // Pirates slowly relocate towards active trade lanes
+                drift_origin(sim, ship_index);
+
+                let mut prey_indices = Vec::new();
+
+                let ship_count = sim.ships.len();
+                for target_index in 0..ship_count {
+                    if sim.is_prey(target_index) {
+                        let ship_pos = sim.ships[ship_index].pos;
+                        let target_ship_pos = sim.ships[target_index].pos;
+                        let dist = ship_pos.distance(target_ship_pos);
+                        if dist < sim.config.pirate_territory() * 0.5 {
+                            prey_indices.push(target_index);
+                        }
+                    }
+                }
+
+                let prey = prey_indices.iter().choose(&mut sim.prng);
+                match prey {
+                    Some(prey_index) => {
+                        let progress = sim.config.raid_variance();
+                        let progress = progress.choose(&mut sim.prng);
+                        let progress = progress.unwrap();
+                        ShipGoal::Hunt { prey: *prey_index, progress }
+                    },
+                    None => ShipGoal::Wander
+                }
+            },
+
+            ShipGoal::Hunt { prey, .. } => {
+                // Seize the prey's cargo
+                sim.ships[prey].cargo = Cargo::Empty;
+
+                ShipGoal::Wander
+            },
+
+            goal => goal
+        }
+    }
+
+    fn allows_goal(&self, sim: &Sim, ship_index: usize, goal: ShipGoal) -> bool {
+        match goal {
+            ShipGoal::Wander | ShipGoal::Scan => true,
+            ShipGoal::Hunt { prey, .. } => {
+                prey != ship_index && prey < sim.ships.len() &&
+                sim.behavior(prey).raidable()
+            },
+            _ => false
+        }
+    }
+
+    fn color(&self, ship: &Ship) -> [f32; 3] {
+        match ship.goal {
+            ShipGoal::Wander | ShipGoal::Scan => [1f32, 0.1f32, 0f32],
+            _ => [1f32, 0f32, 0f32]
+        }
+    }
+
+    // Pirates circle slowly, it takes a while for a patrol to show
+    fn trail(&self) -> Trail { Trail { length: 180, opacity: 0.6f32 } }
+}
+
+/// Moves a pirate's origin towards the nearest lane
+/// currently used by a trader carrying cargo
+fn drift_origin(sim: &mut Sim, ship_index: usize) {
+    let origin = sim.ships[ship_index].origin;
+
+    let mut nearest: Option<Point2<f32>> = None;
+    for (other_index, ship) in sim.ships.iter().enumerate() {
+        if let ShipGoal::Visit { target } = ship.goal {
+            if !sim.is_prey(other_index) {
+                continue;
+            }
+
+            let lane_pos = crate::sim::nearest_on_segment(
+                origin,
+                ship.pos,
+                sim.system[target].pos);
+
+            if nearest.is_none_or(|n| {
+                origin.distance2(lane_pos) < origin.distance2(n)
+            } ) {
+                nearest = Some(lane_pos);
+            }
+        }
+    }
+
+    if let Some(lane_pos) = nearest {
+        let drift = sim.config.pirate_drift();
+        sim.ships[ship_index].origin = Point2::new(
+            origin.x + (lane_pos.x - origin.x) * drift,
+            origin.y + (lane_pos.y - origin.y) * drift);
+    }
+}
@@ -0,0 +1,147 @@
+use rand::{
+    seq::SliceRandom,
+    rngs::SmallRng
+};
+
+use crate::sim::{
+    Sim,
+    ship::{Ship, ShipGoal, ShipIntent, Cargo},
+    planet::{Planet, PlanetFeature}
+};
+
+use super::{
+    ShipBehavior,
+    Trail,
+    stock
+};
+
+/// Carries ore from well-stocked stations to those running low
+/// Traders are spawned by stations, so they aren't populated up front
+pub struct Trader;
+
+impl ShipBehavior for Trader {
+    fn name(&self) -> &'static str { "trader" }
+
+    fn update(&self, sim: &Sim, ship_index: usize, rng: &mut SmallRng) -> ShipIntent {
+        match sim.ships[ship_index].goal {
+            ShipGoal::Visit { target } => {
+                // Traders bend their path around known hotspots
+                let mut ship = sim.ships[ship_index].clone();
+                let dest_pos = sim.danger.detour(
+                    ship.pos,
+                    sim.system[target].pos,
+                    sim.config.danger_rad());
+
+                let complete = sim.approach(&mut ship, target, dest_pos);
+                ShipIntent::new(ship, complete)
+            },
+            _ => sim.follow_goal(ship_index, rng)
+        }
+    }
+
+    fn next_goal(&self, sim: &mut Sim, ship_index: usize) -> ShipGoal {
+        let target = match sim.ships[ship_index].goal {
+            ShipGoal::Visit { target } => target,
+            goal => return goal
+        };
+
+        // Jump to another system after reaching a gate
+        // The galaxy moves the ship once this update is over
+        if let Some(PlanetFeature::Gate { .. }) = sim.system[target].feat {
+            if !sim.departed.contains(&ship_index) {
+                sim.departed.push(ship_index);
+            }
+
+            return ShipGoal::Visit { target };
+        }
+
+        // Deliver ore if the Trader was carrying them
+        let cargo = sim.ships[ship_index].cargo != Cargo::Empty;
+        if cargo {
+            *stock(&mut sim.system[target]) += 1;
+            sim.ships[ship_index].cargo = Cargo::Empty;
+        }
+
+        // Find the ship's new destination
+        let dest;
+
+        // Gates don't hold any stock, so anything can be exported
+        fn dest_stock(pl: &Planet) -> usize {
+            match pl.feat {
+                Some(PlanetFeature::Station { stock }) => stock,
+                _ => 0
+            }
+        }
+
+        { // Randomly select it from all planets with stations or gates
+            let mut stations = sim.station_indices();
+            stations.retain(|pl| *pl != target);
+            stations.append(&mut sim.gate_indices());
+
+            // Stations that are short on ore are worth more,
+            // routes through known hotspots are worth less
+            let target_res = *stock(&mut sim.system[target]);
+            let ship_pos = sim.ships[ship_index].pos;
+            let weight = |&pl_index: &usize| {
+                let dest_res = dest_stock(&sim.system[pl_index]);
+
+                let mut reward = if cargo { 1f32 } else {
+                    1f32 + target_res.saturating_sub(dest_res) as f32
+                };
+
+                if let Some(PlanetFeature::Gate { .. }) = sim.system[pl_index].feat {
+                    reward *= sim.config.gate_reward();
+                }
+
+                let danger = sim.danger.route_danger(
+                    ship_pos,
+                    sim.system[pl_index].pos,
+                    sim.config.danger_rad());
+
+                reward / (1f32 + danger * sim.config.danger_weight())
+            };
+
+            dest = *stations
+                .choose_weighted(&mut sim.prng, weight)
+                .unwrap();
+        }
+
+        #[allow(clippy::blocks_in_conditions)]
+        if { // Determine if the ship should carry ore
+            let target_res = *stock(&mut sim.system[target]);
+            let dest_res = dest_stock(&sim.system[dest]);
+
+            // Should carry ore if destination has less
+            // AND if it didn't carry any to this station
+            target_res > dest_res && !cargo
+        } {
+            // Take ore from station and give to ship
+            *stock(&mut sim.system[target]) -= 1;
+            sim.ships[ship_index].cargo = Cargo::Ore;
+        }
+
+        ShipGoal::Visit { target: dest }
+    }
+
+    fn allows_goal(&self, sim: &Sim, _ship_index: usize, goal: ShipGoal) -> bool {
+        match goal {
+            ShipGoal::Visit { target } => matches!(
+                sim.system.get(target).and_then(|pl| pl.feat.as_ref()),
+                Some(PlanetFeature::Station { .. } | PlanetFeature::Gate { .. })
+            ),
+            _ => false
+        }
+    }
+
+    fn raidable(&self) -> bool { true }
+
+    fn color(&self, ship: &Ship) -> [f32; 3] {
+        match ship.cargo {
+            Cargo::Empty => [0f32, 0.6f32, 1f32],
+            _ => [0f32, 1f32, 0.6f32]
+        }
+    }
+
+    // Long enough to show the trade lanes between stations
+    fn trail(&self) -> Trail { Trail { length: 120, opacity: 0.5f32 } }
+}
@@ -0,0 +1,127 @@
+use cgmath::{
+    Point2,
+    MetricSpace
+};
+
+/// A single region of remembered danger
+#[derive(Copy, Clone)]
+pub struct Hotspot {
+    pub pos: Point2<f32>,
+    pub heat: f32
+}
+
+/// Shared knowledge of where raids happened and pirates were seen
+/// Hotspots cool down every tick and are forgotten once cold
+#[derive(Clone, Default)]
+pub struct DangerMap {
+    pub hotspots: Vec<Hotspot>
+}
+
+impl DangerMap {
+    // Heat below which a hotspot is forgotten
+    const COLD: f32 = 0.01;
+
+    // Number of points sampled along a route
+    const ROUTE_SAMPLES: usize = 8;
+
+    /// Records danger at the given position
+    /// Merges into an existing hotspot if one is within `rad`
+    pub fn report(&mut self, pos: Point2<f32>, heat: f32, rad: f32) {
+        let nearest = self.hotspots.iter_mut().find(|spot| {
+            spot.pos.distance(pos) < rad
+        } );
+
+        match nearest {
+            Some(spot) => {
+                // Pull the hotspot towards the new report
+                let weight = heat / (spot.heat + heat);
+                spot.pos.x += (pos.x - spot.pos.x) * weight;
+                spot.pos.y += (pos.y - spot.pos.y) * weight;
+                spot.heat += heat;
+            },
+            None => self.hotspots.push(Hotspot { pos, heat })
+        }
+    }
+
+    /// Cools every hotspot, forgetting those that have gone cold
+    pub fn decay(&mut self, rate: f32) {
+        for spot in self.hotspots.iter_mut() {
+            spot.heat *= rate;
+        }
+
+        self.hotspots.retain(|spot| spot.heat > Self::COLD);
+    }
+
+    /// Danger felt at a single point
+    /// Each hotspot falls off linearly to zero at `rad`
+    pub fn danger_at(&self, pos: Point2<f32>, rad: f32) -> f32 {
+        self.hotspots.iter().fold(0f32, |danger, spot| {
+            let dist = spot.pos.distance(pos);
+            danger + spot.heat * (1f32 - dist / rad).max(0f32)
+        } )
+    }
+
+    /// Average danger along the straight path between two points
+    pub fn route_danger(&self, from: Point2<f32>, to: Point2<f32>, rad: f32) -> f32 {
+        let mut danger = 0f32;
+        for i in 0..=Self::ROUTE_SAMPLES {
+            let t = i as f32 / Self::ROUTE_SAMPLES as f32;
+            let pos = Point2::new(
+                from.x + (to.x - from.x) * t,
+                from.y + (to.y - from.y) * t);
+            danger += self.danger_at(pos, rad);
+        }
+
+        danger / (Self::ROUTE_SAMPLES + 1) as f32
+    }
+
+    /// Returns a point to steer towards on the way to `to`
+    /// If the hottest spot near the path is in the way,
+    /// the returned point is pushed out of its radius
+    pub fn detour(&self, from: Point2<f32>, to: Point2<f32>, rad: f32) -> Point2<f32> {
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        let len2 = dx * dx + dy * dy;
+        if len2 == 0f32 {
+            return to;
+        }
+
+        let mut worst: Option<(Point2<f32>, f32)> = None;
+        for spot in self.hotspots.iter() {
+            // There is no going around danger at either end of the path
+            if spot.pos.distance(from) < rad || spot.pos.distance(to) < rad {
+                continue;
+            }
+
+            // Closest point on the path to the hotspot
+            let closest = super::nearest_on_segment(spot.pos, from, to);
+
+            // Ignore hotspots that the path doesn't pass through
+            let dist = closest.distance(spot.pos);
+            if dist >= rad {
+                continue;
+            }
+
+            let threat = spot.heat * (1f32 - dist / rad);
+            if worst.is_none_or(|(.., w)| threat > w) {
+                worst = Some((spot.pos, threat));
+            }
+        }
+
+        match worst {
+            Some((spot_pos, ..)) => {
+                // Steer to whichever side of the hotspot the path already leans
+                let len = len2.sqrt();
+                let (mut nx, mut ny) = (-dy / len, dx / len);
+                let side = (spot_pos.x - from.x) * nx + (spot_pos.y - from.y) * ny;
+                if side > 0f32 {
+                    nx = -nx;
+                    ny = -ny;
+                }
+
+                Point2::new(spot_pos.x + nx * rad, spot_pos.y + ny * rad)
+            },
+            None => to
+        }
+    }
+}
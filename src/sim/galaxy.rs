@@ -0,0 +1,236 @@
+use rand::{
+    Rng,
+    SeedableRng,
+    seq::SliceRandom,
+    rngs::StdRng
+};
+
+use rayon::prelude::*;
+
+use cgmath::{
+    Point2,
+    MetricSpace
+};
+
+use super::{
+    Sim,
+    SimConfig,
+    ship::ShipGoal,
+    behavior::{ShipBehavior, Pirate},
+    planet::{BodyKind, PlanetFeature}
+};
+
+#[derive(Clone)]
+pub struct GalaxyConfig {
+    galaxy_seed: Option<u64>,
+    system_count: usize,
+    system_spacing: f32,
+    extra_link_prob: f64,
+    system_config: SimConfig
+}
+
+impl Default for GalaxyConfig {
+    fn default() -> Self {
+        Self {
+            galaxy_seed: None,
+            system_count: 5,
+            system_spacing: 0.5,
+            extra_link_prob: 0.3,
+            system_config: SimConfig::default()
+        }
+    }
+}
+
+impl GalaxyConfig {
+    /// Makes every galaxy built from this config generate the same systems
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.galaxy_seed = Some(seed);
+        self
+    }
+
+    /// Config shared by every system, their seeds are picked by the galaxy
+    pub fn with_system_config(mut self, system_config: SimConfig) -> Self {
+        self.system_config = system_config;
+        self
+    }
+}
+
+/// Several systems connected by pairs of jump gates
+#[derive(Clone)]
+pub struct Galaxy {
+    pub systems: Vec<Sim>,
+    pub positions: Vec<Point2<f32>>,
+    pub links: Vec<(usize, usize)>
+}
+
+impl Default for Galaxy {
+    fn default() -> Self {
+        let config = GalaxyConfig::default();
+        Self::new(config)
+    }
+}
+
+impl Galaxy {
+    pub fn new(config: GalaxyConfig) -> Self {
+        let mut prng = match config.galaxy_seed {
+            Some(s) => SeedableRng::seed_from_u64(s),
+            None => StdRng::from_entropy()
+        };
+
+        // Each system is generated from its own seed, derived from the galaxy's
+        let mut systems = Vec::new();
+        for _ in 0..config.system_count {
+            let mut system_config = config.system_config.clone();
+            system_config.system_seed = Some(prng.gen());
+            systems.push(Sim::new(system_config));
+        }
+
+        // Place systems on the galaxy map, keeping them apart if possible
+        let mut positions: Vec<Point2<f32>> = Vec::new();
+        for _ in 0..config.system_count {
+            let mut pos = super::rand_pos(&mut prng, 1f32);
+            for _ in 0..32 {
+                if positions.iter().all(|other| {
+                    other.distance(pos) > config.system_spacing
+                } ) {
+                    break;
+                }
+
+                pos = super::rand_pos(&mut prng, 1f32);
+            }
+
+            positions.push(pos);
+        }
+
+        // Link every system to its nearest predecessor, so all are reachable
+        // Occasionally add a second link to form loops
+        let mut links = Vec::new();
+        for index in 1..config.system_count {
+            let mut others: Vec<usize> = (0..index).collect();
+            others.sort_by(|&a, &b| {
+                let dist_a = positions[index].distance2(positions[a]);
+                let dist_b = positions[index].distance2(positions[b]);
+                dist_a.partial_cmp(&dist_b).unwrap_or(std::cmp::Ordering::Equal)
+            } );
+
+            links.push((index, others[0]));
+            if others.len() > 1 && prng.gen_bool(config.extra_link_prob) {
+                links.push((index, others[1]));
+            }
+        }
+
+        // Both ends of a link need a free planet to hold the gate
+        links.retain(|&(a, b)| {
+            let gate_a = free_planet(&systems[a], &mut prng);
+            let gate_b = free_planet(&systems[b], &mut prng);
+            if let (Some(gate_a), Some(gate_b)) = (gate_a, gate_b) {
+                systems[a].system[gate_a].feat = Some(
+                    PlanetFeature::Gate { system: b, gate: gate_b }
+                );
+                systems[b].system[gate_b].feat = Some(
+                    PlanetFeature::Gate { system: a, gate: gate_a }
+                );
+
+                true
+            } else {
+                false
+            }
+        } );
+
+        for sim in systems.iter_mut() {
+            lurk_near_gates(sim);
+        }
+
+        Self {
+            systems,
+            positions,
+            links
+        }
+    }
+
+    pub fn update(&mut self) {
+        // Systems don't share any state until ships are transferred
+        self.systems.par_iter_mut().for_each(Sim::update);
+
+        // Move ships that reached a gate to the other end of the link
+        for from in 0..self.systems.len() {
+            for mut ship in self.systems[from].take_departed() {
+                let gate = match ship.goal {
+                    ShipGoal::Visit { target } => target,
+                    _ => panic!()
+                };
+
+                if let Some(
+                    PlanetFeature::Gate { system, gate }
+                ) = self.systems[from].system[gate].feat {
+                    let dest = &mut self.systems[system];
+                    ship.pos = dest.system[gate].pos;
+                    ship.speed = ship.initial_speed;
+
+                    // Head for the nearest station to sell the cargo
+                    let stations = super::nearest_with_feature(
+                        &dest.system,
+                        Some(PlanetFeature::Station { stock: 0 } ),
+                        ship.pos);
+                    ship.goal = ShipGoal::Visit { target: stations[0] };
+
+                    dest.ships.push(ship);
+                }
+            }
+        }
+    }
+}
+
+/// Picks a planet without a feature to hold a new gate
+/// Ore planets are used only if the system has ore to spare
+fn free_planet(sim: &Sim, prng: &mut StdRng) -> Option<usize> {
+    let planets: Vec<usize> = (0..sim.system.len())
+        .filter(|&pl_index| sim.system[pl_index].kind == BodyKind::Planet)
+        .collect();
+
+    let barren: Vec<usize> = planets.iter()
+        .copied()
+        .filter(|&pl_index| sim.system[pl_index].feat.is_none())
+        .collect();
+
+    if let Some(&pl_index) = barren.choose(prng) {
+        return Some(pl_index);
+    }
+
+    let ores: Vec<usize> = planets.into_iter()
+        .filter(|&pl_index| {
+            matches!(sim.system[pl_index].feat, Some(PlanetFeature::Ore { .. }))
+        } )
+        .collect();
+
+    match ores.len() {
+        0 | 1 => None,
+        _ => ores.choose(prng).copied()
+    }
+}
+
+/// Moves pirates so that their territory surrounds the system's gates
+/// One pirate is assigned to each gate, while there are pirates to spare
+fn lurk_near_gates(sim: &mut Sim) {
+    // Planets have no positions until their orbits are first updated
+    for root_index in sim.root_indices() {
+        sim.update_planet_pos(root_index);
+    }
+
+    let gates = super::gate_indices(&sim.system);
+    let pirate = sim.jobs().find(Pirate.name());
+    let pirates: Vec<usize> = (0..sim.ships.len())
+        .filter(|&ship_index| {
+            Some(sim.ships[ship_index].job) == pirate
+        } )
+        .collect();
+
+    for (gate, ship_index) in gates.into_iter().zip(pirates) {
+        let origin = sim.system[gate].pos;
+        let offset = super::rand_pos(&mut sim.prng, sim.config.pirate_territory);
+
+        let pirate = &mut sim.ships[ship_index];
+        pirate.origin = origin;
+        pirate.pos = Point2::new(origin.x + offset.x, origin.y + offset.y);
+    }
+}
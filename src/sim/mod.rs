@@ -1,8 +1,15 @@
 pub mod ship;
 pub mod planet;
+pub mod danger;
+pub mod galaxy;
+pub mod script;
+pub mod behavior;
+pub mod validate;
 
 use std::{
     f32::consts::{PI, TAU},
+    path::Path,
+    sync::Arc,
     ops::Range,
     mem::discriminant,
     cmp::Ordering::Equal 
@@ -12,9 +19,12 @@ use rand::{
     Rng, 
     SeedableRng, 
     seq::IteratorRandom, 
-    rngs::StdRng 
+    seq::SliceRandom, 
+    rngs::{StdRng, SmallRng}
 };
 
+use rayon::prelude::*;
+
 use cgmath::{
     Point2, 
     MetricSpace, 
@@ -26,14 +36,27 @@ use strum::IntoEnumIterator;
 
 use ship::{
     Ship,
-    ShipJob,
-    ShipGoal 
+    ShipGoal,
+    ShipIntent,
+    ShipEffect,
+    Cargo,
+    JobId
 };
 
 use planet::{
     Planet,
     Orbit,
-    PlanetFeature 
+    PlanetFeature,
+    BodyKind 
+};
+
+use danger::DangerMap;
+
+use script::ShipScripts;
+
+use behavior::{
+    ShipBehavior,
+    ShipRegistry
 };
 
 #[derive(Clone)]
@@ -41,9 +64,26 @@ pub struct SimConfig {
     system_rad: f32,
     system_seed: Option<u64>,
     sun_rad: f32,
+    binary_prob: f64,
+    binary_size_multiplier: Range<f32>,
+    binary_sep: f32,
+    binary_speed: f32,
+    s_type_limit: f32,
     pl_moon_prob: f64,
     pl_feat_prob: f64,
     pl_size_multiplier: Range<f32>,
+    belt_prob: f64,
+    belt_width: f32,
+    belt_density: f32,
+    belt_size_multiplier: Range<f32>,
+    belt_richness: f32,
+    comet_count: usize,
+    comet_perihelion: Range<f32>,
+    comet_aphelion: Range<f32>,
+    comet_size_multiplier: Range<f32>,
+    comet_speed: f32,
+    inner_system: f32,
+    ice_value: usize,
     ship_speed: f32,
     ship_acceleration: f32,
     ship_cost: usize,
@@ -55,18 +95,45 @@ pub struct SimConfig {
     raid_range: f32,
     raid_duration: usize,
     raid_variance: Range<isize>,
-    death_prob: f64
+    death_prob: f64,
+    danger_rad: f32,
+    danger_decay: f32,
+    danger_weight: f32,
+    raid_heat: f32,
+    sighting_heat: f32,
+    pirate_drift: f32,
+    gate_reward: f32,
+    scripts: Option<Arc<ShipScripts>>,
+    jobs: Arc<ShipRegistry>,
+    validate_ticks: bool
 }
 
 impl Default for SimConfig {
     fn default() -> Self {
-        Self {
+        let mut config = Self {
             system_rad: 2.0,
             system_seed: None,
             sun_rad: 0.1,
+            binary_prob: 0.3,
+            binary_size_multiplier: 0.4..0.9,
+            binary_sep: 1.0,
+            binary_speed: 0.1,
+            s_type_limit: 0.4,
             pl_moon_prob: 0.5,
             pl_feat_prob: 0.8,
             pl_size_multiplier: 0.1..0.3,
+            belt_prob: 0.25,
+            belt_width: 0.12,
+            belt_density: 150.0,
+            belt_size_multiplier: 0.03..0.06,
+            belt_richness: 0.3,
+            comet_count: 2,
+            comet_perihelion: 0.15..0.4,
+            comet_aphelion: 1.2..2.0,
+            comet_size_multiplier: 0.1..0.2,
+            comet_speed: 0.05,
+            inner_system: 0.6,
+            ice_value: 4,
             ship_speed: 0.005,
             ship_acceleration: 1.05,
             ship_cost: 4,
@@ -78,17 +145,167 @@ impl Default for SimConfig {
             raid_range: 0.2,
             raid_duration: 40,
             raid_variance: -20..20,
-            death_prob: 0.4
-        }
+            death_prob: 0.4,
+            danger_rad: 0.3,
+            danger_decay: 0.995,
+            danger_weight: 4.0,
+            raid_heat: 1.0,
+            sighting_heat: 0.02,
+            pirate_drift: 0.005,
+            gate_reward: 0.5,
+            scripts: None,
+            jobs: Arc::new(ShipRegistry::default()),
+            validate_ticks: true
+        };
+
+        // Built-in jobs, the sim and galaxy find them by name
+        config.register_job(behavior::Trader);
+        config.register_job(behavior::Miner);
+        config.register_job(behavior::Pirate);
+
+        config
     }
 }
 
+impl SimConfig {
+    /// Makes every sim built from this config generate the same system
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.system_seed = Some(seed);
+        self
+    }
+
+    /// Upper bound on the radius of generated systems
+    pub fn with_system_rad(mut self, system_rad: f32) -> Self {
+        self.system_rad = system_rad;
+        self
+    }
+
+    /// Number of miners each system starts with
+    pub fn with_miner_count(mut self, miner_count: usize) -> Self {
+        self.miner_count = miner_count;
+        self
+    }
+
+    /// Loads behavior scripts from the given directory, see ShipScripts::load
+    /// Built-in behavior is used for jobs without a script
+    pub fn with_scripts(mut self, dir: &Path) -> Self {
+        let scripts = ShipScripts::load(dir);
+        self.scripts = (!scripts.is_empty()).then(|| Arc::new(scripts));
+        self
+    }
+
+    /// Whether to check Sim::validate after every tick
+    /// Only has an effect in debug builds
+    pub fn with_tick_validation(mut self, validate_ticks: bool) -> Self {
+        self.validate_ticks = validate_ticks;
+        self
+    }
+
+    /// Replaces every job, built-in ones included
+    /// Without a trader, stations never launch new ships
+    pub fn with_jobs(mut self, jobs: ShipRegistry) -> Self {
+        self.jobs = Arc::new(jobs);
+        self
+    }
+
+    /// Adds a new kind of ship to every sim built from this config
+    /// Its ships are added by ShipBehavior::populate
+    pub fn register_job(&mut self, behavior: impl ShipBehavior + 'static) -> JobId {
+        Arc::make_mut(&mut self.jobs).register(behavior)
+    }
+
+    /// Every job that ships built from this config can have
+    pub fn jobs(&self) -> &ShipRegistry {
+        &self.jobs
+    }
+
+    /// Speed ships start each trip at
+    pub fn ship_speed(&self) -> f32 {
+        self.ship_speed
+    }
+
+    /// Factor a ship's speed grows by on each tick it heads straight for its target
+    pub fn ship_acceleration(&self) -> f32 {
+        self.ship_acceleration
+    }
+
+    /// Progress a Wait goal has to reach to be complete, one a tick
+    pub fn harvest_duration(&self) -> usize {
+        self.harvest_duration
+    }
+
+    /// Range a Wait goal's starting progress is picked from
+    pub fn harvest_variance(&self) -> Range<isize> {
+        self.harvest_variance.clone()
+    }
+
+    /// Stock a station gains for a load of ice, ore is worth 1
+    pub fn ice_value(&self) -> usize {
+        self.ice_value
+    }
+
+    /// How far pirates roam from their origin
+    pub fn pirate_territory(&self) -> f32 {
+        self.pirate_territory
+    }
+
+    /// Distance at which a pirate can raid its prey
+    pub fn raid_range(&self) -> f32 {
+        self.raid_range
+    }
+
+    /// Progress a Hunt goal has to pass once in range to be complete
+    pub fn raid_duration(&self) -> usize {
+        self.raid_duration
+    }
+
+    /// Range a Hunt goal's starting progress is picked from
+    pub fn raid_variance(&self) -> Range<isize> {
+        self.raid_variance.clone()
+    }
+
+    /// Radius to pass to DangerMap lookups
+    pub fn danger_rad(&self) -> f32 {
+        self.danger_rad
+    }
+
+    /// How strongly danger puts traders off a route
+    pub fn danger_weight(&self) -> f32 {
+        self.danger_weight
+    }
+
+    /// Fraction of the way a pirate's origin moves towards a trade lane after each scan
+    pub fn pirate_drift(&self) -> f32 {
+        self.pirate_drift
+    }
+
+    /// Factor applied to the reward for leaving through a gate
+    pub fn gate_reward(&self) -> f32 {
+        self.gate_reward
+    }
+}
+
+/// Notable things that happened during the last update
+/// Nothing in the sim depends on these, they're kept for display
+#[derive(Clone, Copy, Debug)]
+pub enum SimEvent {
+    Launched { station: usize },
+    Destroyed { job: JobId },
+    Departed { job: JobId, gate: usize }
+}
+
+/// Cloning is cheap enough to snapshot a running sim,
+/// and the clone continues exactly as the original would
+#[derive(Clone)]
 pub struct Sim {
     pub prng: StdRng,
     pub system: Vec<Planet>,
     pub system_rad: f32,
     pub ships: Vec<Ship>,
     pub killed: Vec<usize>,
+    pub departed: Vec<usize>,
+    pub events: Vec<SimEvent>,
+    pub danger: DangerMap,
     pub config: SimConfig
 }
 
@@ -121,39 +338,130 @@ impl Sim {
             total_rad(system, pl_index) + system[pl_index].rad + rad * 3f32
         }
 
-        // Create an StdRng object from a seed, if it is provided
-        let mut prng = match config.system_seed {
-            Some(s) => SeedableRng::seed_from_u64(s),
-            None => StdRng::from_entropy()
-        };
-
-        // Initialize system with sun
-        let mut system = vec![Planet::new(config.sun_rad)];
-        
-        loop { // Populate system with planet subsystems
+        // Builds a planet and its moons, without attaching an orbit
+        // Returns the planet's index and the total radius of its subsystem
+        fn build_subsystem(system: &mut Vec<Planet>, prng: &mut StdRng, config: &SimConfig) -> (usize, f32) {
             let pl_index = system.len();
             let mut pl_rad = config.sun_rad;
             pl_rad *= prng.gen_range(config.pl_size_multiplier.clone());
             system.push(Planet::new(pl_rad));
 
             while { 
-                total_rad(&system, pl_index) < system[pl_index].rad * 5f32 &&
+                total_rad(system, pl_index) < system[pl_index].rad * 5f32 &&
                 prng.gen_bool(config.pl_moon_prob) 
             } {
                 let mult = prng.gen_range(config.pl_size_multiplier.clone());
                 let moon_rad = pl_rad * mult;
                 let moon_index = system.len();
 
-                let dist = padded_total_rad(&system, pl_index, moon_rad);
-                let moon_orbit = Orbit::new(pl_index, dist);
+                let dist = padded_total_rad(system, pl_index, moon_rad);
+                let moon_orbit = Orbit::new(pl_index, dist, prng);
 
                 system[pl_index].moon_indices.push(moon_index);
                 system.push(Planet::new(moon_rad));
                 system[moon_index].orbit = Some(moon_orbit);
             }
 
-            // Total radius of the planet subsystem
-            let pl_system_rad = padded_total_rad(&system, pl_index, pl_rad);
+            (pl_index, padded_total_rad(system, pl_index, pl_rad))
+        }
+
+        // Create an StdRng object from a seed, if it is provided
+        let mut prng = match config.system_seed {
+            Some(s) => SeedableRng::seed_from_u64(s),
+            None => StdRng::from_entropy()
+        };
+
+        // Initialize system with sun
+        let mut system = vec![Planet::new(config.sun_rad)];
+        system[0].kind = BodyKind::Star;
+
+        if prng.gen_bool(config.binary_prob) {
+            // The sun is replaced by a pair of stars orbiting a barycenter
+            system[0].kind = BodyKind::Barycenter;
+            system[0].rad = 0f32;
+
+            let mult = prng.gen_range(config.binary_size_multiplier.clone());
+            let star_rads = [config.sun_rad, config.sun_rad * mult];
+
+            // The heavier star stays closer to the barycenter
+            let mut orbit = Orbit::new(0, 0f32, &mut prng);
+            orbit.speed = config.binary_speed;
+            for (i, &star_rad) in star_rads.iter().enumerate() {
+                let other_rad = star_rads[1 - i];
+                let star_index = system.len();
+                let mut star = Planet::new(star_rad);
+                star.kind = BodyKind::Star;
+                star.orbit = Some(Orbit {
+                    dist: config.binary_sep * other_rad / (star_rad + other_rad),
+                    angle: orbit.angle + PI * i as f32,
+                    ..orbit
+                } );
+
+                system[0].moon_indices.push(star_index);
+                system.push(star);
+            }
+
+            // S-type planets orbit only one of the stars
+            // Their orbits must stay well clear of the companion
+            for star_index in [1, 2] {
+                loop {
+                    let (pl_index, pl_system_rad) = build_subsystem(
+                        &mut system, 
+                        &mut prng, 
+                        &config);
+
+                    let dist = total_rad(&system, star_index) + pl_system_rad;
+                    if dist + pl_system_rad > config.binary_sep * config.s_type_limit {
+                        system.truncate(pl_index);
+                        break;
+                    }
+
+                    system[star_index].moon_indices.push(pl_index);
+                    system[pl_index].orbit = Some(Orbit::new(star_index, dist, &mut prng));
+                }
+            }
+        }
+        
+        loop { // Populate system with planet subsystems
+            // Occasionally separate subsystems with an asteroid belt
+            if system.len() > 1 && prng.gen_bool(config.belt_prob) {
+                let belt_inner = total_rad(&system, 0) + config.belt_width * 0.5;
+                let belt_outer = belt_inner + config.belt_width;
+                if belt_outer > config.system_rad {
+                    break;
+                }
+
+                // Number of asteroids is proportional to the belt's area
+                let area = PI * (belt_outer.powi(2) - belt_inner.powi(2));
+                let count = (area * config.belt_density) as usize;
+
+                // Every asteroid circles the sun in the same direction as the rest of its belt,
+                // but at its own distance and phase
+                let belt_orbit = Orbit::new(0, belt_inner, &mut prng);
+                for _ in 0..count {
+                    let mult = prng.gen_range(config.belt_size_multiplier.clone());
+                    let ast_index = system.len();
+                    let mut ast = Planet::new(config.sun_rad * mult);
+                    ast.kind = BodyKind::Asteroid;
+                    ast.feat = Some(PlanetFeature::Ore { 
+                        richness: config.belt_richness 
+                    } );
+                    ast.orbit = Some(Orbit {
+                        dist: prng.gen_range(belt_inner..belt_outer),
+                        angle: prng.gen_range(0f32..TAU),
+                        ..belt_orbit
+                    } );
+
+                    system[0].moon_indices.push(ast_index);
+                    system.push(ast);
+                }
+            }
+
+            // Circumbinary planets orbit the barycenter of both stars
+            let (pl_index, pl_system_rad) = build_subsystem(
+                &mut system, 
+                &mut prng, 
+                &config);
 
             // If the new system exceeds the SimConfig field 'system_rad'
             // Remove it and break
@@ -167,17 +475,28 @@ impl Sim {
             // Lastly, attach an orbit to the origin of the new subsystem
             system[0].moon_indices.push(pl_index);
             system[pl_index].orbit = Some(
-                Orbit::new(0, system_rad + pl_system_rad)
+                Orbit::new(0, system_rad + pl_system_rad, &mut prng)
             );
         };
 
+        // Ice is reserved for comets, gates are placed by the galaxy
         fn rand_feature(prng: &mut StdRng) -> PlanetFeature {
-            PlanetFeature::iter().choose(prng).unwrap()
+            PlanetFeature::iter()
+                .filter(|feat| {
+                    !matches!(feat, PlanetFeature::Ice | PlanetFeature::Gate { .. })
+                } )
+                .choose(prng)
+                .unwrap()
         }
 
-        // Must be at least 4 planets for the ships to have proper behavior
-        // The sun, 2 planets with stations, 1 with ore
-        if system.len() < 4 {
+        // Only planets and moons can hold stations
+        let pl_indices: Vec<usize> = (0..system.len())
+            .filter(|&pl_index| system[pl_index].kind == BodyKind::Planet)
+            .collect();
+
+        // Must be at least 3 planets for the ships to have proper behavior
+        // 2 planets with stations, 1 with ore
+        if pl_indices.len() < 3 {
             panic!()
         }
 
@@ -186,21 +505,33 @@ impl Sim {
                 PlanetFeature::Station { stock: 0 } 
             }
 
-            fn new_ore_feature() -> PlanetFeature { 
-                PlanetFeature::Ore 
+            // Larger bodies are richer, relative to an average-sized planet
+            fn new_ore_feature(config: &SimConfig, pl_rad: f32) -> PlanetFeature { 
+                let mult = config.pl_size_multiplier.clone();
+                let avg_rad = config.sun_rad * (mult.start + mult.end) * 0.5;
+                PlanetFeature::Ore { richness: (pl_rad / avg_rad).sqrt() }
             }
 
             // Ensure that planets with essential features are present
-            let last_pl_index = system.len() - 1;
-            let rand_pl_index = prng.gen_range(2..system.len());
-            system[1].feat = Some(new_station());
+            let first_pl_index = pl_indices[0];
+            let last_pl_index = pl_indices[pl_indices.len() - 1];
+            let rand_pl_index = *pl_indices[1..].choose(&mut prng).unwrap();
+            system[first_pl_index].feat = Some(new_station());
             system[last_pl_index].feat = Some(new_station());
-            system[rand_pl_index].feat = Some(new_ore_feature());
+            system[rand_pl_index].feat = Some(
+                new_ore_feature(&config, system[rand_pl_index].rad)
+            );
 
             // Randomly add PlanetFeatures throughout the system
-            for pl in system.iter_mut().skip(1) {
+            for &pl_index in pl_indices.iter() {
+                let pl = &mut system[pl_index];
                 if prng.gen_bool(config.pl_feat_prob) && pl.feat.is_none() {
-                    pl.feat = Some(rand_feature(&mut prng));
+                    pl.feat = Some(match rand_feature(&mut prng) {
+                        PlanetFeature::Ore { .. } => {
+                            new_ore_feature(&config, pl.rad)
+                        },
+                        feat => feat
+                    } );
                 }
             }
         }
@@ -208,106 +539,208 @@ impl Sim {
         // The ACTUAL radius of the system, in contrast to config.system_rad
         let system_rad = total_rad(&system, 0);
 
-        let mut ships = Vec::new();
-        for _ in 0..config.miner_count {
-            let mut ship = Ship::new(ShipJob::Miner, config.ship_speed);
-            // Use polar coordinates to ensure even distribution
-            ship.pos = rand_pos(&mut prng, system_rad);
-
-            // Add ship after updating position
-            ships.push(ship);
-        }
-
-        {
-            // Ships start at random points, with random destinations
-            // Initial goals are specific to each ship's job
-            let ores = ore_indices(&system);
-            for ship in ships.iter_mut() {
-                ship.goal = ShipGoal::Visit { 
-                    target: *ores.iter().choose(&mut prng).unwrap()
-                };
-            }
+        // Comets are added last so they don't count towards system_rad
+        // Both ends of each orbit are given relative to system_rad
+        for _ in 0..config.comet_count {
+            let peri_dist = prng.gen_range(config.comet_perihelion.clone());
+            let peri_dist = peri_dist * system_rad;
+            let aph_dist = prng.gen_range(config.comet_aphelion.clone());
+            let aph_dist = aph_dist * system_rad;
+
+            let mut orbit = Orbit::new(0, (peri_dist + aph_dist) * 0.5, &mut prng);
+            orbit.ecc = (aph_dist - peri_dist) / (aph_dist + peri_dist);
+            orbit.peri = prng.gen_range(0f32..TAU);
+            orbit.speed = config.comet_speed;
+
+            let mult = prng.gen_range(config.comet_size_multiplier.clone());
+            let mut comet = Planet::new(config.sun_rad * mult);
+            comet.kind = BodyKind::Comet;
+            comet.feat = Some(PlanetFeature::Ice);
+            comet.orbit = Some(orbit);
+
+            let comet_index = system.len();
+            system[0].moon_indices.push(comet_index);
+            system.push(comet);
         }
 
-        // Generate a few pirate ships to steal from traders
-        for _ in 0..config.pirate_count {
-            let pirate_pos = rand_pos(&mut prng, system_rad * 0.5);
-            let mut pirate = Ship::new(
-                ShipJob::Pirate { origin: (pirate_pos.x, pirate_pos.y) }, 
-                config.ship_speed);
-            pirate.pos = pirate_pos;
-            pirate.goal = ShipGoal::Wander; // pirates start by wandering
-
-            // Move the pirate to a random spot within its territory
-            let offset = rand_pos(&mut prng, config.pirate_territory);
-            pirate.pos.x += offset.x;
-            pirate.pos.y += offset.y;
-
-            // Add pirate after giving it a random pos
-            ships.push(pirate);
-        }
-
-        Self {
+        let mut sim = Self {
             prng,
             system,
             system_rad,
-            ships,
+            ships: Vec::new(),
             killed: Vec::new(),
+            departed: Vec::new(),
+            events: Vec::new(),
+            danger: DangerMap::default(),
             config
-        }        
+        };
+
+        // Each job adds its own initial ships
+        let jobs = sim.config.jobs.clone();
+        for job in jobs.jobs() {
+            jobs.get(job).populate(&mut sim, job);
+        }
+
+        sim
     }
 
     pub fn update(&mut self) {
-        // Update positions of all planets
-        self.update_planet_pos(0);
-
-        // Spawn new ships from stations with sufficient stock
-        for pl_index in 0..self.system.len() {
-            if let Some(
-                PlanetFeature::Station { ref mut stock } 
-            ) = self.system[pl_index].feat {
-                if *stock > self.config.ship_cost {
-                    *stock -= self.config.ship_cost;
-                    let mut ship = Ship::new(
-                        ShipJob::Trader { cargo: false }, 
-                        self.config.ship_speed);
-                    ship.pos = self.system[pl_index].pos;
-                    ship.goal = ShipGoal::Visit { target: pl_index };
-
-                    self.ships.push(ship);
+        self.events.clear();
+
+        // Update positions of all planets, starting from each root
+        for root_index in self.root_indices() {
+            self.update_planet_pos(root_index);
+        }
+
+        // Old raids and sightings are slowly forgotten
+        self.danger.decay(self.config.danger_decay);
+
+        // Spawn new traders from stations with sufficient stock
+        if let Some(trader) = self.config.jobs.find(behavior::Trader.name()) {
+            for pl_index in 0..self.system.len() {
+                if let Some(
+                    PlanetFeature::Station { ref mut stock } 
+                ) = self.system[pl_index].feat {
+                    if *stock > self.config.ship_cost {
+                        *stock -= self.config.ship_cost;
+                        let mut ship = Ship::new(
+                            trader,
+                            self.config.ship_speed,
+                            &mut self.prng);
+                        ship.pos = self.system[pl_index].pos;
+                        ship.goal = ShipGoal::Visit { target: pl_index };
+
+                        self.ships.push(ship);
+                        self.events.push(SimEvent::Launched { station: pl_index });
+                    }
                 }
             }
         }
 
-        // Update every ship
-        for ship_index in 0..self.ships.len() {
-            self.update_ship(ship_index);
+        // Every ship decides what to do against the same snapshot of the sim
+        // Each gets its own rng, so the result doesn't depend on thread count
+        let tick_seed: u64 = self.prng.gen();
+        let intents: Vec<ShipIntent> = (0..self.ships.len())
+            .into_par_iter()
+            .with_min_len(64)
+            .map(|ship_index| {
+                let seed = tick_seed.wrapping_add(ship_index as u64);
+                let mut rng = SmallRng::seed_from_u64(seed);
+                self.behavior(ship_index).update(self, ship_index, &mut rng)
+            } )
+            .collect();
+
+        // Intents are then applied in ship order
+        let mut effects = Vec::new();
+        let mut completed = Vec::new();
+        for (ship_index, intent) in intents.into_iter().enumerate() {
+            self.ships[ship_index] = intent.ship;
+            effects.extend(intent.effects);
+            if intent.complete {
+                completed.push(ship_index);
+            }
+        }
+
+        for effect in effects {
+            self.apply_effect(effect);
+        }
+
+        // Scripts all see the sim as it was before any goals changed this tick
+        let world = match (&self.config.scripts, completed.is_empty()) {
+            (Some(..), false) => Some(self.world_view()),
+            _ => None
+        };
+
+        // If the ship has achieved its goal, Self::update_ship_goal is called
+        for ship_index in completed {
+            self.update_ship_goal(ship_index, world.as_ref());
         }
 
         // Kill all trading ships that were destroyed in raids this update cycle
-        for index in self.killed.drain(0..) {
-            for ship in self.ships.iter_mut() {
-                if let ShipGoal::Hunt { ref mut prey, .. } = ship.goal {
-                    if *prey == index {
-                        ship.goal = ShipGoal::Wander;
-                    } else if *prey > index {
-                        *prey -= 1;
-                    }
+        while let Some(index) = self.killed.pop() {
+            let ship = self.remove_ship(index);
+            self.events.push(SimEvent::Destroyed { job: ship.job });
+        }
+
+        // Departing ships are only removed once the galaxy moves them
+        for &ship_index in self.departed.iter() {
+            let ship = &self.ships[ship_index];
+            if let ShipGoal::Visit { target } = ship.goal {
+                self.events.push(SimEvent::Departed { job: ship.job, gate: target });
+            }
+        }
+
+        // Catch broken state on the tick it happens, not when it's next used
+        if cfg!(debug_assertions) && self.config.validate_ticks {
+            if let Err(report) = self.validate() {
+                panic!("{}", report);
+            }
+        }
+    }
+
+    fn apply_effect(&mut self, effect: ShipEffect) {
+        match effect {
+            ShipEffect::Jam { ship } => {
+                // Prevent target ship from accelerating
+                self.ships[ship].speed = self.ships[ship].initial_speed;
+            },
+            ShipEffect::Kill { ship } => {
+                if !self.killed.contains(&ship) {
+                    self.killed.push(ship);
                 }
+            },
+            ShipEffect::Report { pos, heat } => {
+                self.danger.report(pos, heat, self.config.danger_rad);
             }
+        }
+    }
 
-            self.ships.remove(index);
+    /// Removes every ship that reached a gate during the last update
+    /// Their goals still point at the gate they left through
+    pub fn take_departed(&mut self) -> Vec<Ship> {
+        let mut departed = Vec::new();
+        while let Some(index) = self.departed.pop() {
+            departed.push(self.remove_ship(index));
         }
+
+        departed
+    }
+
+    /// Removes a ship, updating every index that refers to the ships after it
+    fn remove_ship(&mut self, index: usize) -> Ship {
+        for ship in self.ships.iter_mut() {
+            if let ShipGoal::Hunt { ref mut prey, .. } = ship.goal {
+                if *prey == index {
+                    ship.goal = ShipGoal::Wander;
+                } else if *prey > index {
+                    *prey -= 1;
+                }
+            }
+        }
+
+        for list in [&mut self.killed, &mut self.departed] {
+            list.retain(|&other| other != index);
+            list.iter_mut()
+                .filter(|other| **other > index)
+                .for_each(|other| *other -= 1);
+        }
+
+        self.ships.remove(index)
+    }
+
+    /// Bodies that don't orbit anything
+    /// Usually just the sun, or the barycenter of a binary system
+    pub fn root_indices(&self) -> Vec<usize> {
+        (0..self.system.len())
+            .filter(|&pl_index| self.system[pl_index].orbit.is_none())
+            .collect()
     }
 
     /// Updates the planet at given index, then recursively updates its moons
-    /// If called on the sun (Self::system[0]), updates the whole system
+    /// If called on a root body, updates its whole hierarchy
     fn update_planet_pos(&mut self, pl_index: usize) {
         fn dist_to_sun(pos: Point2<f32>, orbit: Orbit) -> f32 {
-            Point2::new(
-                pos.x + orbit.dist * orbit.angle.cos(),
-                pos.y + orbit.dist * orbit.angle.sin()
-            ).distance(
+            (pos + orbit.offset()).distance(
                 (0f32, 0f32).into()
             )
         }
@@ -324,9 +757,11 @@ impl Sim {
             offset *= pl_orbit.speed;
 
             { // Relative size affects angle offset
-                let sun_rad = self.system[0].rad;
-                let pl_rad = self.system[pl_orbit.parent_index].rad;
-                offset *= sun_rad / pl_rad;
+                // Barycenters have no size, so they count as a sun
+                let parent = &self.system[pl_orbit.parent_index];
+                if parent.kind != BodyKind::Barycenter {
+                    offset *= self.config.sun_rad / parent.rad;
+                }
             }
 
             // Reverse if the orbit is counterclockwise
@@ -337,9 +772,15 @@ impl Sim {
                 temp_orbit.angle += offset;
                 temp_orbit.angle %= TAU;
                 dist_to_sun(parent_pos, temp_orbit) };
-            if pl_orbit.parent_index == 0 {
+            if pl_orbit.ecc > 0f32 {
+                // Eccentric orbits sweep out equal areas in equal times
+                let r = pl_orbit.radius();
+                offset *= (1f32 - pl_orbit.ecc.powi(2)).sqrt();
+                offset *= (pl_orbit.dist / r).powi(2);
+            } else if self.system[pl_orbit.parent_index].orbit.is_none() && 
+                self.system[pl_index].kind != BodyKind::Star {
                 // The nearer a planet is, the FASTER it goes
-                // Doesn't apply to moons
+                // Doesn't apply to moons, or to stars which must stay paired
                 offset *= (self.system_rad - dist).sqrt() / self.system_rad; 
             }
 
@@ -348,9 +789,7 @@ impl Sim {
             pl_orbit.angle %= TAU;
 
             // Update orbit and pos
-            self.system[pl_index].pos = Point2::new(
-                parent_pos.x + pl_orbit.dist * pl_orbit.angle.cos(),
-                parent_pos.y + pl_orbit.dist * pl_orbit.angle.sin());
+            self.system[pl_index].pos = parent_pos + pl_orbit.offset();
             self.system[pl_index].orbit = Some(pl_orbit);
         }
 
@@ -373,70 +812,101 @@ impl Sim {
         
     }
 
-    /// Updates ship position and checks the status of its goal
-    /// If the ship has achieved its goal, Self::update_ship_goal is called
-    fn update_ship(&mut self, ship_index: usize) {     
-        fn arrived(ship_pos: Point2<f32>, old_ship_pos: Point2<f32>, pl_pos: Point2<f32>, pl_rad: f32) -> bool {
-            /* ship_pos.distance(pl_pos) <= pl_rad * 2f32 */
-            let old_x = old_ship_pos.x - pl_pos.x;
-            let old_y = old_ship_pos.y - pl_pos.y;
-            let new_x = ship_pos.x - pl_pos.x;
-            let new_y = ship_pos.y - pl_pos.y;
-
-            let a = (new_x - old_x).powf(2f32) + (new_y - old_y).powf(2f32);
-            let b = 2f32 * (old_x * (new_x - old_x) + old_y * (new_y - old_y));
-            let c = old_x.powf(2f32) + old_y.powf(2f32) - pl_rad.powf(2f32);
-            let disc = b.powf(2f32) - 4f32 * a * c;
-            if disc <= 0f32 {
-                return false;
-            }
+    /// The behavior that drives the ship at the given index
+    pub fn behavior(&self, ship_index: usize) -> &dyn ShipBehavior {
+        self.config.jobs.get(self.ships[ship_index].job)
+    }
 
-            let disc = disc.sqrt();
-            let t1 = (b * -1f32 + disc) / (2f32 * a);
-            let t2 = (b * -1f32 - disc) / (2f32 * a);
-            if (0f32 < t1 && t1 < 1f32) || (0f32 < t2 && t2 < 1f32) {
-                return true;
-            }
+    /// Every job that ships in this sim can have
+    pub fn jobs(&self) -> &ShipRegistry {
+        &self.config.jobs
+    }
+
+    /// Stock a station spends on each ship it launches
+    pub fn ship_cost(&self) -> usize {
+        self.config.ship_cost
+    }
+
+    /// Whether pirates would currently raid the ship
+    pub fn is_prey(&self, ship_index: usize) -> bool {
+        self.ships[ship_index].cargo != Cargo::Empty &&
+        self.behavior(ship_index).raidable()
+    }
+
+    /// Every planet with a feature of the same kind as filter, or without one for None
+    /// The contents of the feature are ignored, so `Station { stock: 0 }` finds every station
+    pub fn planets_with_feature(&self, filter: Option<PlanetFeature>) -> Vec<usize> {
+        filter_system(&self.system, filter)
+    }
+
+    /// Same as planets_with_feature, sorted nearest to pos first
+    pub fn nearest_with_feature(&self, filter: Option<PlanetFeature>, pos: Point2<f32>) -> Vec<usize> {
+        nearest_with_feature(&self.system, filter, pos)
+    }
+
+    pub fn station_indices(&self) -> Vec<usize> {
+        station_indices(&self.system)
+    }
+
+    pub fn gate_indices(&self) -> Vec<usize> {
+        gate_indices(&self.system)
+    }
 
-            false
+    pub fn ore_indices(&self) -> Vec<usize> {
+        ore_indices(&self.system)
+    }
+
+    /// Moves the ship towards dest_pos on its way to the given planet
+    /// Ships only accelerate while heading straight for it,
+    /// and slow down once when they first turn aside to avoid danger
+    /// Returns true if the ship arrived at the planet
+    pub fn approach(&self, ship: &mut Ship, pl_index: usize, dest_pos: Point2<f32>) -> bool {
+        let pl_pos = self.system[pl_index].pos;
+        let pl_rad = self.system[pl_index].rad;
+
+        let old_ship_pos = ship.pos;
+
+        update_ship_pos(ship, dest_pos);
+
+        let detouring = dest_pos != pl_pos;
+        if !detouring {
+            ship.speed *= self.config.ship_acceleration;
+        } else if !ship.detouring {
+            ship.speed = ship.initial_speed;
         }
 
-        fn update_ship_pos(ship: &mut Ship, dest_pos: Point2<f32>) {
-            // Position offsets
-            let dx = dest_pos.x - ship.pos.x;
-            let dy = dest_pos.y - ship.pos.y;
+        ship.detouring = detouring;
 
-            // Update position, angle and increase speed
-            ship.pos.x += dx * ship.speed;
-            ship.pos.y += dy * ship.speed;
-            ship.angle = Rad::atan2(dx, dy).0 + PI;
+        // Every trip starts from a standstill
+        if arrived(ship.pos, old_ship_pos, pl_pos, pl_rad) {
+            ship.speed = ship.initial_speed;
+            ship.detouring = false;
+            return true;
         }
 
+        false
+    }
+
+    /// Updates ship position and checks the status of its goal
+    /// This is the movement shared by all built-in jobs
+    pub fn follow_goal(&self, ship_index: usize, rng: &mut SmallRng) -> ShipIntent {
+        let mut ship = self.ships[ship_index].clone();
+        let mut effects = Vec::new();
+
         let mut ship_objective_complete = false;
-        match self.ships[ship_index].goal {
+        match ship.goal {
             ShipGoal::Visit { target: pl_index } => {
                 // Update ship objective IFF it has reached its destination
                 let pl_pos = self.system[pl_index].pos;
-                let pl_rad = self.system[pl_index].rad;
-
-                let old_ship_pos = self.ships[ship_index].pos;
-                // Update ship position and increase speed
-                let mut ship = &mut self.ships[ship_index];
-                update_ship_pos(ship, pl_pos);
-                ship.speed *= self.config.ship_acceleration;
-
-                if arrived(ship.pos, old_ship_pos, pl_pos, pl_rad) {
-                    ship.speed = ship.initial_speed; // reset speed
-                    ship_objective_complete = true;
-                }
+                ship_objective_complete = self.approach(&mut ship, pl_index, pl_pos);
             },
 
             ShipGoal::Wait { target: pl_index, progress } => {
                 // Ships dock on planets while waiting
-                self.ships[ship_index].pos = self.system[pl_index].pos;
-                self.ships[ship_index].goal = ShipGoal::Wait { 
-                    target: pl_index, 
-                    progress: progress + 1 
+                ship.pos = self.system[pl_index].pos;
+                ship.goal = ShipGoal::Wait {
+                    target: pl_index,
+                    progress: progress + 1
                 };
 
                 // Update ship objective if the ship is done mining
@@ -446,29 +916,26 @@ impl Sim {
             },
 
             ShipGoal::Wander => {
-                let mut ship = &mut self.ships[ship_index];
-
                 // Reverse direction upon reaching edge of territory
-                if let ShipJob::Pirate { origin } = ship.job {
-                    let dist = ship.pos.distance(origin.into());
-                    if dist > self.config.pirate_territory {
-                        update_ship_pos(ship, origin.into());
-                    } else {
-                        // Change heading slightly
-                        let mut angle_offset = 0.0348f32;
-                        if self.prng.gen_bool(0.5) { angle_offset *= -1.0; }
-
-                        // Keep moving forward
-                        ship.angle += angle_offset;
-                        ship.pos.x += (ship.angle + 1.566).cos() * ship.speed;
-                        ship.pos.y -= (ship.angle + 1.566).sin() * ship.speed;
-
-                        // Always update, scan every other tick
-                        ship_objective_complete = true;
-                    }
+                let origin = ship.origin;
+                let dist = ship.pos.distance(origin);
+                if dist > self.config.pirate_territory {
+                    update_ship_pos(&mut ship, origin);
+                } else {
+                    // Change heading slightly
+                    let mut angle_offset = 0.0348f32;
+                    if rng.gen_bool(0.5) { angle_offset *= -1.0; }
+
+                    // Keep moving forward
+                    ship.angle += angle_offset;
+                    ship.pos.x += (ship.angle + 1.566).cos() * ship.speed;
+                    ship.pos.y -= (ship.angle + 1.566).sin() * ship.speed;
+
+                    // Always update, scan every other tick
+                    ship_objective_complete = true;
                 }
             },
-            
+
             ShipGoal::Scan => {
                 // Update no matter what after the scan cycle
                 ship_objective_complete = true;
@@ -477,189 +944,134 @@ impl Sim {
             ShipGoal::Hunt { prey, progress } => {
                 // Move towards the prey ship
                 let prey_pos = self.ships[prey].pos;
-                update_ship_pos(&mut self.ships[ship_index], prey_pos);
+                update_ship_pos(&mut ship, prey_pos);
 
                 // Check if the target is still a valid target for a raid
-                let prey_dist = self.ships[ship_index].pos.distance(prey_pos);
-                if let ShipJob::Trader { cargo } = self.ships[prey].job {
-                    if !cargo { 
-                        ship_objective_complete = true; 
-                    } else if prey_dist < self.config.raid_range {
-                        // Prevent target ship from accelerating
-                        let initial_speed = self.ships[prey].initial_speed;
-                        self.ships[prey].speed = initial_speed;
-                        self.ships[ship_index].goal = ShipGoal::Hunt {
-                            prey,
-                            progress: progress + 1
-                        };
-    
-                        // Raid is complete
-                        if progress > self.config.raid_duration as isize {
-                            if self.prng.gen_bool(self.config.death_prob) && !self.killed.contains(&prey) {
-                                self.killed.push(prey);
-                            }
+                let prey_dist = ship.pos.distance(prey_pos);
+                if !self.is_prey(prey) {
+                    ship_objective_complete = true;
+                } else if prey_dist < self.config.raid_range {
+                    // The jammed ship reports the pirate's position
+                    effects.push(ShipEffect::Jam { ship: prey });
+                    effects.push(ShipEffect::Report {
+                        pos: ship.pos,
+                        heat: self.config.sighting_heat
+                    } );
+                    ship.goal = ShipGoal::Hunt {
+                        prey,
+                        progress: progress + 1
+                    };
 
-                            ship_objective_complete = true;
+                    // Raid is complete
+                    if progress > self.config.raid_duration as isize {
+                        effects.push(ShipEffect::Report {
+                            pos: prey_pos,
+                            heat: self.config.raid_heat
+                        } );
+
+                        if rng.gen_bool(self.config.death_prob) {
+                            effects.push(ShipEffect::Kill { ship: prey });
                         }
-                    } else {
-                        // Reset goal if the ship escaped
-                        self.ships[ship_index].goal = ShipGoal::Wander;
+
+                        ship_objective_complete = true;
                     }
-                } 
+                } else {
+                    // Reset goal if the ship escaped
+                    ship.goal = ShipGoal::Wander;
+                }
             }
         }
 
-        if ship_objective_complete {
-            self.update_ship_goal(ship_index)
-        }
+        ShipIntent { ship, complete: ship_objective_complete, effects }
     }
 
     /// Assumes that the ship has achieved its previous goal
-    fn update_ship_goal(&mut self, ship_index: usize) {
-        // Returns a mutable reference to the `stock` field of a station
-        // Panics if given planet doesn't have a station
-        fn stock(pl: &mut Planet) -> &mut usize {
-            if let Some(PlanetFeature::Station { ref mut stock } ) = pl.feat {
-                return stock;
-            }
-        
-            panic!()
-        }
-        
-        // All ship logic occurs in this match expression
-        let job = self.ships[ship_index].job;
-        let goal = self.ships[ship_index].goal;
-        self.ships[ship_index].goal = match (job, goal) {
-            (
-                ShipJob::Trader { cargo }, 
-                ShipGoal::Visit { target } 
-            ) => {
-                // Deliver ore if the Trader was carrying them
-                if cargo {
-                    *stock(&mut self.system[target]) += 1;
-                    self.ships[ship_index].job = ShipJob::Trader { 
-                        cargo: false 
-                    };
-                }
+    /// `world` is what scripts see of the sim, there's none without scripts
+    fn update_ship_goal(&mut self, ship_index: usize, world: Option<&rhai::Dynamic>) {
+        // All job-specific logic lives in the ship's behavior
+        // It's handed the sim mutably, so the registry is kept alive separately
+        let jobs = self.config.jobs.clone();
+        let default_goal = jobs.get(self.ships[ship_index].job).next_goal(self, ship_index);
+
+        // Behavior scripts may override the built-in goal
+        // Ships leaving through a gate are already committed
+        self.ships[ship_index].goal = match (world, self.departed.contains(&ship_index)) {
+            (Some(world), false) => {
+                self.scripted_goal(ship_index, default_goal, world)
+                    .unwrap_or(default_goal)
+            },
+            _ => default_goal
+        };
+    }
+
+    /// Whether the body is close enough to the sun to be reached
+    /// Only comets ever leave the inner system
+    pub fn in_inner_system(&self, pl_index: usize) -> bool {
+        self.comet_brightness(pl_index) >= 1f32
+    }
 
-                // Find the ship's new destination
-                let dest;
+    /// Comets brighten as they approach the sun
+    /// Fully visible in the inner system, invisible well outside of it
+    pub fn comet_brightness(&self, pl_index: usize) -> f32 {
+        let pl = &self.system[pl_index];
+        if pl.kind != BodyKind::Comet {
+            return 1f32;
+        }
 
-                { // Randomly select it from all planets with stations
-                    let mut stations = station_indices(&self.system);
-                    stations.retain(|pl| *pl != target);
-                    dest = *stations.iter().choose(&mut self.prng).unwrap();
-                }
-                
-                #[allow(clippy::blocks_in_if_conditions)]
-                if { // Determine if the ship should carry ore
-                    let target_res = *stock(&mut self.system[target]);
-                    let dest_res = *stock(&mut self.system[dest]);
-
-                    // Should carry ore if destination has less
-                    // AND if it didn't carry any to this station
-                    target_res > dest_res && !cargo
-                } {
-                    // Take ore from station and give to ship
-                    *stock(&mut self.system[target]) -= 1;
-                    self.ships[ship_index].job = ShipJob::Trader { 
-                        cargo: true 
-                    };
-                }                     
-                
-                ShipGoal::Visit { target: dest }
-            },
+        let inner_rad = self.system_rad * self.config.inner_system;
+        let dist = pl.pos.distance((0f32, 0f32).into());
+        ((inner_rad * 1.5 - dist) / (inner_rad * 0.5)).clamp(0f32, 1f32)
+    }
+}
 
-            ( // After arriving at station or mining site
-                ShipJob::Miner, 
-                ShipGoal::Visit { target } 
-            ) => {
-                // Behavior depends on the type of planet is just visited
-                match self.system[target].feat.as_ref().unwrap() {
-                    PlanetFeature::Station { .. } => {
-                        // Deposit ore at the station
-                        *stock(&mut self.system[target]) += 1;
-
-                        // Visit another planet with ore
-                        let ores = nearest_with_feature(
-                            &self.system, 
-                            Some(PlanetFeature::Ore), 
-                            self.ships[ship_index].pos);
-                        ShipGoal::Visit { target: ores[0] }
-                    },
-                    PlanetFeature::Ore => {
-                        // Pause to mine
-                        let progress = self.config.harvest_variance.clone();
-                        let progress = progress.choose(&mut self.prng);
-                        let progress = progress.unwrap();
-                        ShipGoal::Wait { target, progress }
-                    }
-                }
-            },
+fn arrived(ship_pos: Point2<f32>, old_ship_pos: Point2<f32>, pl_pos: Point2<f32>, pl_rad: f32) -> bool {
+    /* ship_pos.distance(pl_pos) <= pl_rad * 2f32 */
+    let old_x = old_ship_pos.x - pl_pos.x;
+    let old_y = old_ship_pos.y - pl_pos.y;
+    let new_x = ship_pos.x - pl_pos.x;
+    let new_y = ship_pos.y - pl_pos.y;
+
+    let a = (new_x - old_x).powf(2f32) + (new_y - old_y).powf(2f32);
+    let b = 2f32 * (old_x * (new_x - old_x) + old_y * (new_y - old_y));
+    let c = old_x.powf(2f32) + old_y.powf(2f32) - pl_rad.powf(2f32);
+    let disc = b.powf(2f32) - 4f32 * a * c;
+    if disc <= 0f32 {
+        return false;
+    }
 
-            (
-                ShipJob::Miner, 
-                ShipGoal::Wait { .. } 
-            ) => {
-                // After mining, the ship needs to deposit
-                let stations = nearest_with_feature(
-                    &self.system, 
-                    Some(PlanetFeature::Station { stock: 0 } ), 
-                    self.ships[ship_index].pos);
-                ShipGoal::Visit { target: stations[0] }
-            },
-            
-            (
-                ShipJob::Pirate { .. },
-                ShipGoal::Wander { .. }
-            ) => ShipGoal::Scan,
-
-            (
-                ShipJob::Pirate { .. },
-                ShipGoal::Scan
-            ) => {
-                let mut prey_indices = Vec::new();
-
-                let ship_count = self.ships.len();
-                for target_index in 0..ship_count {
-                    let target_job = self.ships[target_index].job;
-                    if let ShipJob::Trader { cargo: true } = target_job {
-                        let ship_pos = self.ships[ship_index].pos;
-                        let target_ship_pos = self.ships[target_index].pos;
-                        let dist = ship_pos.distance(target_ship_pos);
-                        if dist < self.config.pirate_territory * 0.5 {
-                            prey_indices.push(target_index);
-                        }
-                    }
-                }
+    let disc = disc.sqrt();
+    let t1 = (-b + disc) / (2f32 * a);
+    let t2 = (-b - disc) / (2f32 * a);
+    if (0f32 < t1 && t1 < 1f32) || (0f32 < t2 && t2 < 1f32) {
+        return true;
+    }
 
-                let prey = prey_indices.iter().choose(&mut self.prng);
-                match prey {
-                    Some(prey_index) => { 
-                        let progress = self.config.raid_variance.clone();
-                        let progress = progress.choose(&mut self.prng);
-                        let progress = progress.unwrap();
-                        ShipGoal::Hunt { prey: *prey_index, progress } 
-                    },
-                    None => ShipGoal::Wander
-                }
-            },
+    false
+}
 
-            (
-                ShipJob::Pirate { .. },
-                ShipGoal::Hunt { prey, .. }
-            ) => {
-                let prey_job = &mut self.ships[prey].job;
-                if let ShipJob::Trader { ref mut cargo } = prey_job {
-                    *cargo = false;
-                }
+fn update_ship_pos(ship: &mut Ship, dest_pos: Point2<f32>) {
+    // Position offsets
+    let dx = dest_pos.x - ship.pos.x;
+    let dy = dest_pos.y - ship.pos.y;
 
-                ShipGoal::Wander
-            },
+    // Update position, angle and increase speed
+    ship.pos.x += dx * ship.speed;
+    ship.pos.y += dy * ship.speed;
+    ship.angle = Rad::atan2(dx, dy).0 + PI;
+}
 
-            _ => self.ships[ship_index].goal
-        };
+fn nearest_on_segment(pos: Point2<f32>, a: Point2<f32>, b: Point2<f32>) -> Point2<f32> {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let len2 = dx * dx + dy * dy;
+    if len2 == 0f32 {
+        return a;
     }
+
+    let t = ((pos.x - a.x) * dx + (pos.y - a.y) * dy) / len2;
+    let t = t.clamp(0f32, 1f32);
+
+    Point2::new(a.x + dx * t, a.y + dy * t)
 }
 
 fn rand_pos(prng: &mut StdRng, rad: f32) -> Point2<f32> {
@@ -673,8 +1085,12 @@ fn station_indices(system: &[Planet]) -> Vec<usize> {
     filter_system(system, Some(PlanetFeature::Station { stock: 0 }))
 }
 
+fn gate_indices(system: &[Planet]) -> Vec<usize> {
+    filter_system(system, Some(PlanetFeature::Gate { system: 0, gate: 0 }))
+}
+
 fn ore_indices(system: &[Planet]) -> Vec<usize> {
-    filter_system(system, Some(PlanetFeature::Ore))
+    filter_system(system, Some(PlanetFeature::Ore { richness: 0f32 }))
 }
 
 fn filter_system(system: &[Planet], filter: Option<PlanetFeature>) -> Vec<usize> {
@@ -2,34 +2,54 @@ use std::f32::consts::TAU;
 use rand::Rng;
 use strum::EnumIter;
 
-#[derive(Copy, Clone)]
+/// An elliptical orbit with the parent at one focus
+/// `dist` is the semi-major axis, `angle` is measured from the parent
+/// Circular orbits have an eccentricity of 0
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Orbit {
     pub parent_index: usize,
     pub dist: f32,
     pub speed: f32,
     pub ccw: bool,
-    pub angle: f32    
+    pub angle: f32,
+    pub ecc: f32,
+    pub peri: f32
 }
 
 impl Orbit {
-    pub fn new(parent_index: usize, dist: f32) -> Self {
-        let mut prng = rand::thread_rng();
+    pub fn new(parent_index: usize, dist: f32, prng: &mut impl Rng) -> Self {
         Self {
             parent_index,
             dist,
             speed: 0.5f32 * prng.gen_range(1..4) as f32,
             ccw: prng.gen_bool(0.5f64),
-            angle: prng.gen_range(0f32..TAU)
+            angle: prng.gen_range(0f32..TAU),
+            ecc: 0f32,
+            peri: 0f32
         }
     }
+
+    /// Current distance from the parent body
+    pub fn radius(&self) -> f32 {
+        let semi_latus = self.dist * (1f32 - self.ecc.powi(2));
+        semi_latus / (1f32 + self.ecc * (self.angle - self.peri).cos())
+    }
+
+    /// Offset from the parent body's position
+    pub fn offset(&self) -> cgmath::Vector2<f32> {
+        let r = self.radius();
+        cgmath::Vector2::new(r * self.angle.cos(), r * self.angle.sin())
+    }
 }
 
+#[derive(Clone, PartialEq, Debug)]
 pub struct Planet {
     pub pos: cgmath::Point2<f32>,
     pub rad: f32,
     pub orbit: Option<Orbit>,
     pub feat: Option<PlanetFeature>,
-    pub moon_indices: Vec<usize>
+    pub moon_indices: Vec<usize>,
+    pub kind: BodyKind
 }
 
 impl Planet {
@@ -39,13 +59,29 @@ impl Planet {
             rad: radius,
             orbit: None,
             feat: None,
-            moon_indices: Vec::new()
+            moon_indices: Vec::new(),
+            kind: BodyKind::Planet
         }
     }
 }
 
-#[derive(EnumIter)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BodyKind {
+    // Invisible center of mass shared by a pair of stars
+    Barycenter,
+    Star,
+    Planet,
+    Asteroid,
+    Comet
+}
+
+#[derive(Copy, Clone, EnumIter, PartialEq, Debug)]
 pub enum PlanetFeature {
     Station { stock: usize },
-    Ore
+    // Richness scales how quickly ore can be harvested
+    Ore { richness: f32 },
+    // Volatiles only found on comets
+    Ice,
+    // Leads to the paired gate in another system of the galaxy
+    Gate { system: usize, gate: usize }
 }
\ No newline at end of file
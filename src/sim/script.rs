@@ -0,0 +1,251 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::Mutex,
+    fs
+};
+
+use rhai::{
+    Engine,
+    AST,
+    Scope,
+    Dynamic,
+    Map,
+    Array
+};
+
+use super::{
+    Sim,
+    ship::{ShipGoal, Cargo},
+    planet::{BodyKind, PlanetFeature}
+};
+
+/// Behavior scripts, one per ship job
+/// Each script defines `fn next_goal(ship, world)`
+/// Returning `()` keeps the goal chosen by the built-in behavior
+pub struct ShipScripts {
+    engine: Engine,
+    scripts: HashMap<String, AST>,
+    // Errors already printed, scripts tend to fail the same way for every ship
+    reported: Mutex<HashSet<String>>
+}
+
+impl ShipScripts {
+    // Steps a single call can take before it's cut off, so a runaway loop can't hang the tick
+    const MAX_OPERATIONS: u64 = 100_000;
+
+    /// Compiles every `<job>.rhai` found in the given directory
+    /// Scripts are matched to jobs by ShipBehavior::name,
+    /// broken ones are reported and skipped
+    pub fn load(dir: &Path) -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(Self::MAX_OPERATIONS);
+
+        let mut scripts = HashMap::new();
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "rhai") {
+                continue;
+            }
+
+            let Some(job) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            let Ok(source) = fs::read_to_string(&path) else {
+                continue;
+            };
+
+            match engine.compile(source) {
+                Ok(ast) => { scripts.insert(job.to_owned(), ast); },
+                Err(e) => eprintln!("{}: {}", path.display(), e)
+            }
+        }
+
+        Self {
+            engine,
+            scripts,
+            reported: Mutex::new(HashSet::new())
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    // Prints each distinct error only the first time it happens
+    fn report(&self, job: &str, error: impl std::fmt::Display) {
+        let message = format!("{} script: {}", job, error);
+        let mut reported = self.reported.lock().unwrap_or_else(|e| e.into_inner());
+        if reported.insert(message.clone()) {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+impl Sim {
+    /// Asks the ship's behavior script for its next goal
+    /// `world` comes from Sim::world_view, shared by every call in a tick
+    /// Returns None if there's no script, it declined, or its goal was invalid
+    pub(super) fn scripted_goal(&self, ship_index: usize, default_goal: ShipGoal, world: &Dynamic) -> Option<ShipGoal> {
+        let scripts = self.config.scripts.as_ref()?;
+        let behavior = self.behavior(ship_index);
+        let ast = scripts.scripts.get(behavior.name())?;
+
+        let ship = self.ship_view(ship_index, default_goal);
+        let result = scripts.engine.call_fn::<Dynamic>(
+            &mut Scope::new(),
+            ast,
+            "next_goal",
+            (ship, world.clone()));
+
+        let goal = match result {
+            Ok(goal) if goal.is_unit() => return None,
+            Ok(goal) => goal,
+            Err(e) => {
+                scripts.report(behavior.name(), e);
+                return None;
+            }
+        };
+
+        let goal = dynamic_to_goal(goal);
+        match goal {
+            // Goals are only accepted if the built-in behavior could follow them
+            Some(goal) if behavior.allows_goal(self, ship_index, goal) => Some(goal),
+            _ => {
+                scripts.report(behavior.name(), "invalid goal");
+                None
+            }
+        }
+    }
+
+    fn ship_view(&self, ship_index: usize, default_goal: ShipGoal) -> Map {
+        let ship = &self.ships[ship_index];
+
+        let mut view = Map::new();
+        view.insert("index".into(), (ship_index as i64).into());
+        view.insert("job".into(), self.behavior(ship_index).name().into());
+        view.insert("x".into(), (ship.pos.x as f64).into());
+        view.insert("y".into(), (ship.pos.y as f64).into());
+        view.insert("speed".into(), (ship.speed as f64).into());
+        view.insert("goal".into(), goal_to_dynamic(ship.goal));
+        view.insert("default_goal".into(), goal_to_dynamic(default_goal));
+        view.insert("cargo".into(), cargo_to_dynamic(ship.cargo));
+        view.insert("origin_x".into(), (ship.origin.x as f64).into());
+        view.insert("origin_y".into(), (ship.origin.y as f64).into());
+
+        view
+    }
+
+    /// Everything scripts can see of the sim
+    /// It's read-only and shared, so passing it to each script call doesn't copy it
+    pub(super) fn world_view(&self) -> Dynamic {
+        let planets: Array = self.system.iter().enumerate().map(|(pl_index, pl)| {
+            let mut view = Map::new();
+            view.insert("index".into(), (pl_index as i64).into());
+            view.insert("x".into(), (pl.pos.x as f64).into());
+            view.insert("y".into(), (pl.pos.y as f64).into());
+            view.insert("rad".into(), (pl.rad as f64).into());
+            view.insert("kind".into(), match pl.kind {
+                BodyKind::Barycenter => "barycenter",
+                BodyKind::Star => "star",
+                BodyKind::Planet => "planet",
+                BodyKind::Asteroid => "asteroid",
+                BodyKind::Comet => "comet"
+            }.into());
+
+            let feature: Dynamic = match pl.feat {
+                Some(PlanetFeature::Station { stock }) => {
+                    view.insert("stock".into(), (stock as i64).into());
+                    "station".into()
+                },
+                Some(PlanetFeature::Ore { richness }) => {
+                    view.insert("richness".into(), (richness as f64).into());
+                    "ore".into()
+                },
+                Some(PlanetFeature::Ice) => "ice".into(),
+                Some(PlanetFeature::Gate { .. }) => "gate".into(),
+                None => Dynamic::UNIT
+            };
+            view.insert("feature".into(), feature);
+            view.insert("inner".into(), self.in_inner_system(pl_index).into());
+
+            view.into()
+        } ).collect();
+
+        let ships: Array = self.ships.iter().enumerate().map(|(ship_index, ship)| {
+            let mut view = Map::new();
+            view.insert("index".into(), (ship_index as i64).into());
+            view.insert("job".into(), self.behavior(ship_index).name().into());
+            view.insert("x".into(), (ship.pos.x as f64).into());
+            view.insert("y".into(), (ship.pos.y as f64).into());
+            view.insert("cargo".into(), cargo_to_dynamic(ship.cargo));
+
+            view.into()
+        } ).collect();
+
+        let danger: Array = self.danger.hotspots.iter().map(|spot| {
+            let mut view = Map::new();
+            view.insert("x".into(), (spot.pos.x as f64).into());
+            view.insert("y".into(), (spot.pos.y as f64).into());
+            view.insert("heat".into(), (spot.heat as f64).into());
+
+            view.into()
+        } ).collect();
+
+        let mut world = Map::new();
+        world.insert("planets".into(), planets.into());
+        world.insert("ships".into(), ships.into());
+        world.insert("danger".into(), danger.into());
+
+        Dynamic::from_map(world).into_read_only().into_shared()
+    }
+}
+
+fn cargo_to_dynamic(cargo: Cargo) -> Dynamic {
+    match cargo {
+        Cargo::Empty => Dynamic::UNIT,
+        Cargo::Ore => "ore".into(),
+        Cargo::Ice => "ice".into()
+    }
+}
+
+fn goal_to_dynamic(goal: ShipGoal) -> Dynamic {
+    let mut view = Map::new();
+    match goal {
+        ShipGoal::Visit { target } => {
+            view.insert("target".into(), (target as i64).into());
+        },
+        ShipGoal::Wait { target, progress } => {
+            view.insert("target".into(), (target as i64).into());
+            view.insert("progress".into(), (progress as i64).into());
+        },
+        ShipGoal::Hunt { prey, progress } => {
+            view.insert("prey".into(), (prey as i64).into());
+            view.insert("progress".into(), (progress as i64).into());
+        },
+        ShipGoal::Wander | ShipGoal::Scan => {  }
+    }
+    view.insert("kind".into(), goal.name().into());
+
+    view.into()
+}
+
+/// Goals are maps with a `kind` and the fields of the matching ShipGoal
+/// Omitted progress counters start at 0
+fn dynamic_to_goal(goal: Dynamic) -> Option<ShipGoal> {
+    let goal = goal.try_cast::<Map>()?;
+    let int = |key: &str| goal.get(key).and_then(|v| v.as_int().ok());
+    let index = |key: &str| int(key).and_then(|v| usize::try_from(v).ok());
+    let progress = int("progress").unwrap_or(0) as isize;
+
+    let kind = goal.get("kind")?.clone().into_string().ok()?;
+    match kind.as_str() {
+        "visit" => Some(ShipGoal::Visit { target: index("target")? }),
+        "wait" => Some(ShipGoal::Wait { target: index("target")?, progress }),
+        "wander" => Some(ShipGoal::Wander),
+        "hunt" => Some(ShipGoal::Hunt { prey: index("prey")?, progress }),
+        "scan" => Some(ShipGoal::Scan),
+        _ => None
+    }
+}
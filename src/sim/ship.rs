@@ -1,40 +1,92 @@
+use std::f32::consts::TAU;
+
 use rand::Rng;
-use strum::EnumIter;
 
+#[derive(Clone, PartialEq, Debug)]
 pub struct Ship {
+    // Random, so it stays unique when ships move between systems
+    pub id: u64,
     pub pos: cgmath::Point2<f32>,
     pub speed: f32,
     pub initial_speed: f32,
     pub angle: f32,
+    // Whether the ship was steering around danger last tick
+    pub detouring: bool,
     pub goal: ShipGoal,
-    pub job: ShipJob,
+    pub job: JobId,
+    pub cargo: Cargo,
+    pub origin: cgmath::Point2<f32>
 }
 
 impl Ship {
-    pub fn new(job: ShipJob, speed: f32) -> Self {
+    pub fn new(job: JobId, speed: f32, prng: &mut impl Rng) -> Self {
         Self {
+            id: prng.gen(),
             pos: (0f32, 0f32).into(),
             speed,
             initial_speed: speed,
-            angle: rand::thread_rng().gen::<f32>() * 6.28,
+            angle: prng.gen::<f32>() * TAU,
+            detouring: false,
             goal: ShipGoal::Visit { target: 0 },
-            job
+            job,
+            cargo: Cargo::Empty,
+            origin: (0f32, 0f32).into()
         }
     }
 }
 
-#[derive(Copy, Clone, EnumIter)]
-pub enum ShipJob {
-    Trader { cargo: bool },
-    Miner,
-    Pirate { origin: (f32, f32) }
+/// Identifies a ship's behavior within the sim's ShipRegistry
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct JobId(pub usize);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Cargo {
+    Empty,
+    Ore,
+    Ice
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum ShipGoal {
     Visit { target: usize },
     Wait { target: usize, progress: isize },
     Wander,
     Hunt { prey: usize, progress: isize },
     Scan
-}
\ No newline at end of file
+}
+
+impl ShipGoal {
+    /// Identifies the kind of goal to scripts and themes
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShipGoal::Visit { .. } => "visit",
+            ShipGoal::Wait { .. } => "wait",
+            ShipGoal::Wander => "wander",
+            ShipGoal::Hunt { .. } => "hunt",
+            ShipGoal::Scan => "scan"
+        }
+    }
+}
+
+/// What a ship wants to do this tick, decided against a snapshot of the sim
+/// Intents are applied in ship order once every ship has made its decision
+pub struct ShipIntent {
+    pub ship: Ship,
+    pub complete: bool,
+    pub effects: Vec<ShipEffect>
+}
+
+impl ShipIntent {
+    /// The ship's state after this tick, with no effect on other ships
+    pub fn new(ship: Ship, complete: bool) -> Self {
+        Self { ship, complete, effects: Vec::new() }
+    }
+}
+
+/// Changes a ship makes to the rest of the sim
+#[derive(Copy, Clone)]
+pub enum ShipEffect {
+    Jam { ship: usize },
+    Kill { ship: usize },
+    Report { pos: cgmath::Point2<f32>, heat: f32 }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+
+use super::{
+    Sim,
+    ship::ShipGoal,
+    planet::PlanetFeature
+};
+
+/// A single broken invariant, with the indices needed to track it down
+#[derive(Debug)]
+pub enum Violation {
+    MissingParent { pl_index: usize, parent_index: usize },
+    MissingMoon { pl_index: usize, moon_index: usize },
+    UnlistedMoon { pl_index: usize, parent_index: usize },
+    MoonOfOther { pl_index: usize, moon_index: usize },
+    OrbitCycle { pl_index: usize },
+    MissingTarget { ship_index: usize, target: usize },
+    WrongFeature { ship_index: usize, target: usize },
+    MissingPrey { ship_index: usize, prey: usize },
+    NanPlanet { pl_index: usize },
+    NanShip { ship_index: usize },
+    NoStation,
+    NoOre
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Violation::MissingParent { pl_index, parent_index } => write!(f,
+                "planet {} orbits missing planet {}", pl_index, parent_index),
+            Violation::MissingMoon { pl_index, moon_index } => write!(f,
+                "planet {} lists missing moon {}", pl_index, moon_index),
+            Violation::UnlistedMoon { pl_index, parent_index } => write!(f,
+                "planet {} orbits planet {}, which doesn't list it as a moon", pl_index, parent_index),
+            Violation::MoonOfOther { pl_index, moon_index } => write!(f,
+                "planet {} lists moon {}, which doesn't orbit it", pl_index, moon_index),
+            Violation::OrbitCycle { pl_index } => write!(f,
+                "planet {} is part of an orbit cycle", pl_index),
+            Violation::MissingTarget { ship_index, target } => write!(f,
+                "ship {} targets missing planet {}", ship_index, target),
+            Violation::WrongFeature { ship_index, target } => write!(f,
+                "ship {} targets planet {}, which lacks a matching feature", ship_index, target),
+            Violation::MissingPrey { ship_index, prey } => write!(f,
+                "ship {} hunts missing ship {}", ship_index, prey),
+            Violation::NanPlanet { pl_index } => write!(f,
+                "planet {} has a NaN position", pl_index),
+            Violation::NanShip { ship_index } => write!(f,
+                "ship {} has a NaN position", ship_index),
+            Violation::NoStation => write!(f, "system has no stations"),
+            Violation::NoOre => write!(f, "system has no ore")
+        }
+    }
+}
+
+/// Every invariant that Sim::validate found broken
+#[derive(Debug)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "sim failed validation with {} violation(s)", self.violations.len())?;
+        for violation in self.violations.iter() {
+            writeln!(f, "  {}", violation)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationReport {  }
+
+impl Sim {
+    /// Checks the structural invariants that the rest of the sim relies on
+    /// Reports all of them at once, rather than stopping at the first
+    pub fn validate(&self) -> Result<(), ValidationReport> {
+        let mut violations = Vec::new();
+
+        let pl_count = self.system.len();
+        for (pl_index, pl) in self.system.iter().enumerate() {
+            if pl.pos.x.is_nan() || pl.pos.y.is_nan() {
+                violations.push(Violation::NanPlanet { pl_index });
+            }
+
+            // Orbits and moon_indices must agree with one another
+            if let Some(orbit) = pl.orbit {
+                let parent_index = orbit.parent_index;
+                if parent_index >= pl_count {
+                    violations.push(Violation::MissingParent { pl_index, parent_index });
+                } else if !self.system[parent_index].moon_indices.contains(&pl_index) {
+                    violations.push(Violation::UnlistedMoon { pl_index, parent_index });
+                }
+            }
+
+            for &moon_index in pl.moon_indices.iter() {
+                match self.system.get(moon_index).and_then(|moon| moon.orbit) {
+                    None if moon_index >= pl_count => {
+                        violations.push(Violation::MissingMoon { pl_index, moon_index });
+                    },
+                    Some(orbit) if orbit.parent_index == pl_index => {  },
+                    _ => violations.push(Violation::MoonOfOther { pl_index, moon_index })
+                }
+            }
+
+            // Following parents must always lead back to a root
+            let mut current = pl_index;
+            let mut steps = 0;
+            while let Some(orbit) = self.system.get(current).and_then(|pl| pl.orbit) {
+                current = orbit.parent_index;
+                steps += 1;
+                if steps > pl_count {
+                    violations.push(Violation::OrbitCycle { pl_index });
+                    break;
+                }
+            }
+        }
+
+        let feat = |pl_index: usize| self.system[pl_index].feat.as_ref();
+        for (ship_index, ship) in self.ships.iter().enumerate() {
+            if ship.pos.x.is_nan() || ship.pos.y.is_nan() {
+                violations.push(Violation::NanShip { ship_index });
+            }
+
+            match ship.goal {
+                ShipGoal::Visit { target } | ShipGoal::Wait { target, .. } if target >= pl_count => {
+                    violations.push(Violation::MissingTarget { ship_index, target });
+                },
+                // Ships only ever visit planets their job has something to do at
+                ShipGoal::Visit { target } if !self.behavior(ship_index).allows_goal(self, ship_index, ship.goal) => {
+                    violations.push(Violation::WrongFeature { ship_index, target });
+                },
+                // Ships only ever wait while harvesting
+                ShipGoal::Wait { target, .. } if !matches!(
+                    feat(target),
+                    Some(PlanetFeature::Ore { .. } | PlanetFeature::Ice)
+                ) => {
+                    violations.push(Violation::WrongFeature { ship_index, target });
+                },
+                ShipGoal::Hunt { prey, .. } if prey >= self.ships.len() || prey == ship_index => {
+                    violations.push(Violation::MissingPrey { ship_index, prey });
+                },
+                _ => {  }
+            }
+        }
+
+        // Traders and miners need somewhere to go
+        if super::station_indices(&self.system).is_empty() {
+            violations.push(Violation::NoStation);
+        }
+
+        if super::ore_indices(&self.system).is_empty() {
+            violations.push(Violation::NoOre);
+        }
+
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(ValidationReport { violations })
+        }
+    }
+}
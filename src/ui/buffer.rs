@@ -0,0 +1,48 @@
+/// A GPU buffer that's rewritten in place every frame
+/// It's only reallocated when the data outgrows it, doubling in size each time
+pub(super) struct DynamicBuffer {
+    buffer: wgpu::Buffer,
+    usage: wgpu::BufferUsages,
+    capacity: wgpu::BufferAddress
+}
+
+impl DynamicBuffer {
+    const INITIAL_CAPACITY: wgpu::BufferAddress = 4096;
+
+    pub(super) fn new(device: &wgpu::Device, usage: wgpu::BufferUsages) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        Self {
+            buffer: Self::allocate(device, usage, Self::INITIAL_CAPACITY),
+            usage,
+            capacity: Self::INITIAL_CAPACITY
+        }
+    }
+
+    fn allocate(device: &wgpu::Device, usage: wgpu::BufferUsages, size: wgpu::BufferAddress) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage,
+            mapped_at_creation: false
+        } )
+    }
+
+    /// Replaces the buffer's contents, growing it first if needed
+    pub(super) fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+
+        let size = data.len() as wgpu::BufferAddress;
+        if size > self.capacity {
+            self.capacity = size.next_power_of_two();
+            self.buffer = Self::allocate(device, self.usage, self.capacity);
+        }
+
+        queue.write_buffer(&self.buffer, 0, data);
+    }
+
+    pub(super) fn slice(&self) -> wgpu::BufferSlice<'_> {
+        self.buffer.slice(..)
+    }
+}
@@ -1,10 +1,12 @@
 use cgmath::{
     Point2,
     Point3,
+    Vector4,
     Matrix4, SquareMatrix
 };
 
-pub(super) struct Camera {
+/// Looks straight down at the system from a height of `zoom`
+pub struct Camera {
     pub(super) pos: Point2<f32>,
     pub(super) zoom: f32,
     pub(super) aspect: f32
@@ -22,7 +24,53 @@ impl Camera {
         0.0, 0.0, 0.5, 1.0,
     );
 
-    fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
+    // Limits on the camera's height above the system
+    const MIN_ZOOM: f32 = 0.15;
+    const MAX_ZOOM: f32 = 4.0;
+
+    pub fn new(pos: Point2<f32>, zoom: f32, aspect: f32) -> Self {
+        Self { pos, zoom, aspect }
+    }
+
+    /// Point on the system's plane that appears at the given normalized device coords
+    pub(super) fn unproject(&self, ndc: Point2<f32>) -> Point2<f32> {
+        let inverse = self.build_view_projection_matrix().invert().unwrap();
+
+        // Cast a ray from the near plane to the far plane
+        let near = inverse * Vector4::new(ndc.x, ndc.y, 0f32, 1f32);
+        let far = inverse * Vector4::new(ndc.x, ndc.y, 1f32, 1f32);
+        let near = near.truncate() / near.w;
+        let far = far.truncate() / far.w;
+
+        // Then find where it crosses z = 0
+        let t = near.z / (near.z - far.z);
+        Point2::new(
+            near.x + (far.x - near.x) * t,
+            near.y + (far.y - near.y) * t
+        )
+    }
+
+    /// Zooms by the given factor, keeping the point under ndc in place
+    pub(super) fn zoom_at(&mut self, ndc: Point2<f32>, factor: f32) {
+        let anchor = self.unproject(ndc);
+        let zoom = (self.zoom * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+
+        // The visible area scales linearly with the camera's height
+        let ratio = zoom / self.zoom;
+        self.pos.x = anchor.x + (self.pos.x - anchor.x) * ratio;
+        self.pos.y = anchor.y + (self.pos.y - anchor.y) * ratio;
+        self.zoom = zoom;
+    }
+
+    /// Moves the camera so that the point under `from` ends up under `to`
+    pub(super) fn drag(&mut self, from: Point2<f32>, to: Point2<f32>) {
+        let from = self.unproject(from);
+        let to = self.unproject(to);
+        self.pos.x -= to.x - from.x;
+        self.pos.y -= to.y - from.y;
+    }
+
+    pub(super) fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
         let pos = Point3::new(self.pos.x, self.pos.y, self.zoom);
         let target = Point3::new(self.pos.x, self.pos.y, 0.0);
 
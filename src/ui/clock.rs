@@ -0,0 +1,85 @@
+use std::time::Instant;
+
+use winit::event::VirtualKeyCode;
+
+/// Decides how many sim ticks to run each frame
+/// Ticks accumulate at a fixed rate, independent of the monitor's refresh rate
+pub(super) struct Clock {
+    paused: bool,
+    speed_index: usize,
+    pending: f32,
+    steps: usize,
+    last_frame: Instant
+}
+
+impl Clock {
+    const SPEEDS: [f32; 7] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+    const NORMAL_SPEED: usize = 2;
+
+    // Ticks per second at 1x
+    const TICK_RATE: f32 = 60.0;
+
+    // Slow frames don't snowball into ever longer bursts of ticks
+    const MAX_TICKS_PER_FRAME: usize = 64;
+
+    pub(super) fn new() -> Self {
+        Self {
+            paused: false,
+            speed_index: Self::NORMAL_SPEED,
+            pending: 0f32,
+            steps: 0,
+            last_frame: Instant::now()
+        }
+    }
+
+    /// Space pauses, period steps a single tick while paused,
+    /// plus and minus change the speed
+    /// Returns true if the key was handled
+    pub(super) fn input(&mut self, keycode: VirtualKeyCode) -> bool {
+        use VirtualKeyCode::*;
+        match keycode {
+            Space => self.paused = !self.paused,
+            Period if self.paused => self.steps += 1,
+            Equals | Plus | NumpadAdd => {
+                self.speed_index = (self.speed_index + 1).min(Self::SPEEDS.len() - 1);
+            },
+            Minus | NumpadSubtract => {
+                self.speed_index = self.speed_index.saturating_sub(1);
+            },
+            _ => return false
+        }
+
+        true
+    }
+
+    /// Rewinding pauses, so the user can look around before resuming
+    pub(super) fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Number of ticks to run for the frame that's about to be drawn
+    pub(super) fn ticks(&mut self) -> usize {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_frame).as_secs_f32();
+        self.last_frame = now;
+
+        if self.paused {
+            self.pending = 0f32;
+            return std::mem::take(&mut self.steps);
+        }
+
+        self.pending += elapsed * Self::TICK_RATE * Self::SPEEDS[self.speed_index];
+        let ticks = (self.pending as usize).min(Self::MAX_TICKS_PER_FRAME);
+        self.pending = (self.pending - ticks as f32).min(1f32);
+
+        ticks
+    }
+
+    /// Current speed, as shown in the window title
+    pub(super) fn label(&self) -> String {
+        match self.paused {
+            true => String::from("paused"),
+            false => format!("{}x", Self::SPEEDS[self.speed_index])
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::VecDeque;
+
+use crate::sim::galaxy::Galaxy;
+
+/// Periodic snapshots of the galaxy, for rewinding the viewer
+/// Ticks between snapshots are recovered by re-simulating from the one before,
+/// which gives the same result since updates are deterministic
+pub(super) struct History {
+    snapshots: VecDeque<(usize, Galaxy)>
+}
+
+impl History {
+    // Ticks between snapshots, and so the most a rewind has to re-simulate
+    const INTERVAL: usize = 300;
+
+    // Oldest snapshots are dropped once there are this many, which covers 10 minutes
+    // Each is a full copy of the galaxy, around 120 KiB for the default one,
+    // so this is some 15 MB, growing with the number of systems and ships
+    const CAPACITY: usize = 120;
+
+    pub(super) fn new() -> Self {
+        Self { snapshots: VecDeque::new() }
+    }
+
+    /// Called with every tick, only keeps a snapshot on every INTERVAL'th
+    pub(super) fn record(&mut self, tick: usize, galaxy: &Galaxy) {
+        if !tick.is_multiple_of(Self::INTERVAL) {
+            return;
+        }
+
+        // Anything recorded after a rewind is about to be replaced
+        while self.snapshots.back().is_some_and(|&(t, _)| t >= tick) {
+            self.snapshots.pop_back();
+        }
+
+        self.snapshots.push_back((tick, galaxy.clone()));
+        if self.snapshots.len() > Self::CAPACITY {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Rebuilds the galaxy as it was at the given tick
+    /// Stops at the oldest snapshot if the tick is too far back
+    /// Returns the tick that was actually reached
+    pub(super) fn rewind(&self, tick: usize) -> Option<(usize, Galaxy)> {
+        let (snapshot_tick, snapshot) = self.snapshots.iter()
+            .rev()
+            .find(|&&(t, _)| t <= tick)
+            .or(self.snapshots.front())?;
+
+        let mut galaxy = snapshot.clone();
+        for _ in *snapshot_tick..tick {
+            galaxy.update();
+        }
+
+        Some((tick.max(*snapshot_tick), galaxy))
+    }
+}
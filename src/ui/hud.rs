@@ -0,0 +1,107 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write,
+    time::Instant
+};
+
+use super::Clock;
+
+use crate::sim::{
+    SimEvent,
+    galaxy::Galaxy,
+    planet::PlanetFeature
+};
+
+/// Overlay with the state of the sim as a whole, toggled with H
+pub(super) struct Hud {
+    pub(super) visible: bool,
+    fps: f32,
+    last_frame: Instant,
+    events: VecDeque<String>
+}
+
+impl Hud {
+    // Only the most recent events are listed
+    const EVENT_COUNT: usize = 6;
+
+    pub(super) fn new() -> Self {
+        Self {
+            visible: true,
+            fps: 0f32,
+            last_frame: Instant::now(),
+            events: VecDeque::new()
+        }
+    }
+
+    /// Called once per frame, to measure the frame rate
+    pub(super) fn frame(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_frame).as_secs_f32();
+        self.last_frame = now;
+
+        // Smoothed, otherwise the number is unreadable
+        if elapsed > 0f32 {
+            self.fps += (elapsed.recip() - self.fps) * 0.05f32;
+        }
+    }
+
+    /// Called after every tick, collects the events of every system
+    pub(super) fn record(&mut self, tick: usize, galaxy: &Galaxy) {
+        for (index, sim) in galaxy.systems.iter().enumerate() {
+            for event in sim.events.iter() {
+                let description = match *event {
+                    SimEvent::Launched { station } => {
+                        format!("station {} launched a ship", station)
+                    },
+                    SimEvent::Destroyed { job } => {
+                        format!("{} destroyed", sim.jobs().get(job).name())
+                    },
+                    SimEvent::Departed { job, gate } => {
+                        format!("{} left through gate {}", sim.jobs().get(job).name(), gate)
+                    }
+                };
+
+                self.events.push_back(format!("{} s{}: {}", tick, index + 1, description));
+                if self.events.len() > Self::EVENT_COUNT {
+                    self.events.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Tick, speed, frame rate, and the ships and stations of the selected system
+    pub(super) fn text(&self, tick: usize, clock: &Clock, galaxy: &Galaxy, system: usize) -> String {
+        let sim = &galaxy.systems[system];
+
+        let mut text = String::new();
+        writeln!(text, "tick {} ({})", tick, clock.label()).unwrap();
+        writeln!(text, "{:.0} fps", self.fps).unwrap();
+
+        // Ship counts are listed in the order the jobs were registered
+        let mut counts = BTreeMap::new();
+        for ship in sim.ships.iter() {
+            *counts.entry(ship.job.0).or_insert(0) += 1;
+        }
+
+        writeln!(text, "\nsystem {}", system + 1).unwrap();
+        for job in sim.jobs().jobs() {
+            let count = counts.get(&job.0).copied().unwrap_or(0);
+            writeln!(text, "{}s: {}", sim.jobs().get(job).name(), count).unwrap();
+        }
+
+        for (pl_index, pl) in sim.system.iter().enumerate() {
+            if let Some(PlanetFeature::Station { stock }) = pl.feat {
+                writeln!(text, "station {}: {} stock", pl_index, stock).unwrap();
+            }
+        }
+
+        if !self.events.is_empty() {
+            writeln!(text, "\nrecent events").unwrap();
+            for event in self.events.iter() {
+                writeln!(text, "{}", event).unwrap();
+            }
+        }
+
+        text
+    }
+}
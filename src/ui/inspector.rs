@@ -0,0 +1,137 @@
+use cgmath::Point2;
+
+use crate::sim::{
+    Sim,
+    ship::{ShipGoal, Cargo},
+    planet::{Planet, PlanetFeature, BodyKind}
+};
+
+use super::Focus;
+
+/// The ship or body at the given point in sim coordinates
+/// Ships are drawn on top, so they're picked first
+/// Anything within `tolerance` counts, so that tiny bodies can still be clicked
+pub(super) fn pick(sim: &Sim, pos: Point2<f32>, tolerance: f32) -> Option<Focus> {
+    use cgmath::MetricSpace;
+
+    // Ships are drawn as triangles 0.05 long, pointing at their position
+    let ships = sim.ships.iter()
+        .map(|ship| (Focus::Ship(ship.id), ship.pos, 0.05f32));
+    let planets = sim.system.iter()
+        .enumerate()
+        .filter(|(_, pl)| pl.kind != BodyKind::Barycenter)
+        .map(|(pl_index, pl)| (Focus::Planet(pl_index), pl.pos, pl.rad));
+
+    let hits = |(focus, hit_pos, rad): (Focus, Point2<f32>, f32)| {
+        let dist = pos.distance(hit_pos);
+        (dist <= rad.max(tolerance)).then_some((focus, dist))
+    };
+
+    let nearest = |a: &(Focus, f32), b: &(Focus, f32)| {
+        a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)
+    };
+
+    ships.filter_map(hits).min_by(nearest)
+        .or_else(|| planets.filter_map(hits).min_by(nearest))
+        .map(|(focus, _)| focus)
+}
+
+/// Details of the selected body or ship, one per line
+/// The focus must still exist, see focus_pos
+pub(super) fn describe(sim: &Sim, focus: Focus) -> String {
+    match focus {
+        Focus::Ship(id) => {
+            let ship_index = super::ship_index(sim, id).unwrap();
+            let ship = &sim.ships[ship_index];
+            let goal = match ship.goal {
+                ShipGoal::Visit { target } => format!("visit planet {}", target),
+                ShipGoal::Wait { target, progress } => {
+                    format!("wait at planet {} ({})", target, progress)
+                },
+                ShipGoal::Wander => String::from("wander"),
+                ShipGoal::Hunt { prey, progress } => {
+                    format!("hunt ship {} ({})", prey, progress)
+                },
+                ShipGoal::Scan => String::from("scan")
+            };
+
+            let target = match ship.goal {
+                ShipGoal::Visit { target } | ShipGoal::Wait { target, .. } => {
+                    feature(&sim.system[target])
+                },
+                ShipGoal::Hunt { prey, .. } => sim.behavior(prey).name().to_owned(),
+                _ => String::from("none")
+            };
+
+            let cargo = match ship.cargo {
+                Cargo::Empty => "none",
+                Cargo::Ore => "ore",
+                Cargo::Ice => "ice"
+            };
+
+            // The index shifts as other ships are removed, the id never does
+            format!(
+                "ship {}\nid: {:016x}\njob: {}\ngoal: {}\ntarget: {}\ncargo: {}\nspeed: {:.4}",
+                ship_index,
+                ship.id,
+                sim.behavior(ship_index).name(),
+                goal,
+                target,
+                cargo,
+                ship.speed)
+        },
+        Focus::Planet(pl_index) => {
+            let pl = &sim.system[pl_index];
+            let kind = match pl.kind {
+                BodyKind::Barycenter => "barycenter",
+                BodyKind::Star => "star",
+                BodyKind::Planet => "planet",
+                BodyKind::Asteroid => "asteroid",
+                BodyKind::Comet => "comet"
+            };
+
+            let orbit = match pl.orbit {
+                Some(orbit) => format!("planet {} at {:.3}", orbit.parent_index, orbit.radius()),
+                None => String::from("none")
+            };
+
+            format!(
+                "planet {} ({})\nradius: {:.4}\norbit: {}\nfeature: {}\nmoons: {}",
+                pl_index,
+                kind,
+                pl.rad,
+                orbit,
+                feature(pl),
+                pl.moon_indices.len())
+        }
+    }
+}
+
+pub(super) fn feature(pl: &Planet) -> String {
+    match pl.feat {
+        Some(PlanetFeature::Station { stock }) => format!("station, stock {}", stock),
+        Some(PlanetFeature::Ore { richness }) => format!("ore, richness {:.2}", richness),
+        Some(PlanetFeature::Ice) => String::from("ice"),
+        Some(PlanetFeature::Gate { system, .. }) => format!("gate to system {}", system),
+        None => String::from("none")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::SimConfig;
+
+    #[test]
+    fn selection_survives_earlier_removals() {
+        let mut sim = Sim::new(SimConfig::default().with_seed(1));
+        let selected = sim.ships[5].clone();
+        let focus = pick(&sim, selected.pos, 0f32).unwrap();
+        assert!(focus == Focus::Ship(selected.id));
+
+        // Removing a ship shifts every later one down a place
+        sim.ships.remove(0);
+        let description = describe(&sim, focus);
+        assert!(description.contains(&format!("id: {:016x}", selected.id)), "{}", description);
+    }
+}
@@ -1,19 +1,14 @@
-use std::hash::Hash;
-
-use rand::Rng;
-use wgpu::util::DeviceExt;
-use rand_seeder::SipHasher;
-
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub(super) struct Vertex {
     pub(super) position: [f32; 3],
-    pub(super) color: [f32; 3]
+    pub(super) color: [f32; 3],
+    pub(super) alpha: f32
 }
 
 impl Vertex {
-    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = { 
-        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3] 
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = { 
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32] 
     };
 
     pub(super) fn description<'a>() -> wgpu::VertexBufferLayout<'a> {
@@ -27,154 +22,308 @@ impl Vertex {
     }
 }
 
-#[derive(Default)]
-pub(super) struct Mesh {
-    pub(super) vertices: Vec<Vertex>,
-    pub(super) indices: Vec<u16>,
+/// Bodies and ships are all drawn as one of a few shapes
+/// Each is a static unit mesh on the GPU, moved, turned and scaled per instance
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(super) enum Shape {
+    Disc,
+    Diamond,
+    Ship
 }
 
-impl Mesh {
-    pub(super) fn from_planet(planet: &crate::sim::planet::Planet) -> Self {
-        Self {
-            vertices: {
-                let color = {
-                    let mut h = SipHasher::new();
-                    planet.rad.to_string().hash(&mut h);
-                    if let Some(o) = &planet.orbit {
-                        o.dist.to_string().hash(&mut h);
-                        o.speed.to_string().hash(&mut h);
-                        o.ccw.hash(&mut h);
-                    }
-
-                    let mut h = h.into_rng();
-
-                    [
-                        h.gen_range(0f32..1f32),
-                        h.gen_range(0f32..1f32),
-                        h.gen_range(0f32..1f32)
-                    ]
-                };
-
-                let mut vertices = Vec::new();
-
-                // 1st add the center point
-                vertices.push(
-                    Vertex {
-                        position: [
-                            planet.pos.x,
-                            planet.pos.y,
-                            0f32
-                        ],
-                        color
-                    }
-                );
-
-                // AND the 1st point on the circumference of the circle
-                vertices.push(
-                    Vertex {
-                        position: [
-                            planet.rad + planet.pos.x,
-                            planet.pos.y,
-                            0f32 
-                        ],
-                        color
-                    }
-                );
-
-                // Add in each slice, one by one
-                for i in (19625..628000).step_by(19625) {
-                    let i = i as f32 * 0.00001f32;
-
-                    vertices.push(
-                        Vertex {
-                            position: [
-                                i.cos() * planet.rad + planet.pos.x,
-                                i.sin() * planet.rad + planet.pos.y,
-                                0f32
-                            ],
-                            color
-                        }
-                    );
+impl Shape {
+    pub(super) const ALL: [Self; 3] = [Self::Disc, Self::Diamond, Self::Ship];
+
+    // A ship is a narrow triangle, 0.05 long with its tip at the ship's position
+    const SHIP_SIZE: f32 = 0.05;
+    const SHIP_HALF_ANGLE: f32 = 0.2617994;
+
+    /// The shape's vertices and indices, centred on the origin with a size of 1
+    pub(super) fn outline(self) -> (Vec<ShapeVertex>, Vec<u32>) {
+        match self {
+            Shape::Disc => {
+                // The center point, then 32 points around the circumference
+                let mut vertices = vec![ShapeVertex { position: [0f32, 0f32], shade: 1f32 }];
+                for i in 0..32 {
+                    let a = i as f32 / 32f32 * std::f32::consts::TAU;
+                    vertices.push(ShapeVertex { position: [a.cos(), a.sin()], shade: 1f32 });
                 }
 
-                vertices
+                let indices = (1..=32u32)
+                    .flat_map(|i| [i, i % 32 + 1, 0])
+                    .collect();
+
+                (vertices, indices)
             },
-            indices: { 
+            Shape::Diamond => (
                 vec![
-                     1,  2,  0,  2,  3,  0,  3,  4,  0,  4,  5,  0, 
-                     5,  6,  0,  6,  7,  0,  7,  8,  0,  8,  9,  0, 
-                     9, 10,  0, 10, 11,  0, 11, 12,  0, 12, 13,  0, 
-                    13, 14,  0, 14, 15,  0, 15, 16,  0, 16, 17,  0, 
-                    17, 18,  0, 18, 19,  0, 19, 20,  0, 20, 21,  0, 
-                    21, 22,  0, 22, 23,  0, 23, 24,  0, 24, 25,  0, 
-                    25, 26,  0, 26, 27,  0, 27, 28,  0, 28, 29,  0, 
-                    29, 30,  0, 30, 31,  0, 31, 32,  0, 32,  1,  0
-                ]
+                    ShapeVertex { position: [1f32, 0f32], shade: 1f32 },
+                    ShapeVertex { position: [0f32, 1f32], shade: 1f32 },
+                    ShapeVertex { position: [-1f32, 0f32], shade: 1f32 },
+                    ShapeVertex { position: [0f32, -1f32], shade: 1f32 }
+                ],
+                vec![0, 1, 2, 2, 3, 0]
+            ),
+            Shape::Ship => {
+                // Points up the y axis, fading to black towards the back
+                let (sin, cos) = Self::SHIP_HALF_ANGLE.sin_cos();
+                (
+                    vec![
+                        ShapeVertex { position: [0f32, 0f32], shade: 1f32 },
+                        ShapeVertex { position: [-sin, cos], shade: 0f32 },
+                        ShapeVertex { position: [sin, cos], shade: 0f32 }
+                    ],
+                    vec![0, 1, 2]
+                )
             }
         }
     }
+}
 
-    pub(super) fn from_ship(ship: &crate::sim::ship::Ship) -> Self {
-        Self {
-            vertices: {
-                use crate::sim::ship::ShipGoal;
-                use crate::sim::ship::ShipJob::*;
-
-                let size = 0.05f32;
-                let color = match ship.job {
-                    Miner => [1f32, 0.2f32, 0.8f32],
-                    Trader { cargo: false } => [0f32, 0.6f32, 1f32],
-                    Trader { cargo: true } => [0f32, 1f32, 0.6f32],
-                    Pirate { .. } if matches!(ship.goal, ShipGoal::Wander) || matches!(ship.goal, ShipGoal::Scan) => [1f32, 0.1f32, 0f32],
-                    Pirate { .. } => [1f32, 0f32, 0f32]
-                };
-        
-                let top_pos = [ ship.pos.x, ship.pos.y, 0f32 ];
-                
-                let min = ship.angle - 0.2617994;
-                let min_pos = [ 
-                    size * min.sin() + ship.pos.x, 
-                    size * min.cos() + ship.pos.y, 
-                    0f32 
-                ];
-
-                let max = ship.angle + 0.2617994;
-                let max_pos = [ 
-                    size * max.sin() + ship.pos.x, 
-                    size * max.cos() + ship.pos.y, 
-                    0f32 
-                ];
-
-        
-                vec![
-                    Vertex { position: top_pos, color },
-                    Vertex { position: min_pos, color: [ 0f32, 0f32, 0f32 ] },
-                    Vertex { position: max_pos, color: [ 0f32, 0f32, 0f32 ] },
-                ]
-            },
-            indices: vec![0, 1, 2]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(super) struct ShapeVertex {
+    pub(super) position: [f32; 2],
+    // Multiplies the instance's color
+    pub(super) shade: f32
+}
+
+impl ShapeVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = {
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32]
+    };
+
+    pub(super) fn description<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// A single body or ship, placed with its shape's unit mesh
+/// Rotation is counterclockwise, in radians
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(super) struct Instance {
+    pub(super) position: [f32; 2],
+    pub(super) rotation: f32,
+    pub(super) scale: f32,
+    pub(super) color: [f32; 3]
+}
+
+impl Instance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] = {
+        wgpu::vertex_attr_array![2 => Float32x2, 3 => Float32, 4 => Float32, 5 => Float32x3]
+    };
+
+    pub(super) fn description<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
         }
     }
 }
 
+/// Free-form triangles, plus instances of each Shape
+/// Triangles are drawn first, then every Disc, Diamond and Ship in that order
+#[derive(Default)]
+pub struct Mesh {
+    pub(super) vertices: Vec<Vertex>,
+    pub(super) indices: Vec<u32>,
+    pub(super) instances: [Vec<Instance>; Shape::ALL.len()]
+}
+
 impl Mesh {
-    pub(super) fn build_vertex_buffer(&self, device: &wgpu::Device) -> wgpu::Buffer {
-        device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents: bytemuck::cast_slice(self.vertices.as_slice()),
-                usage: wgpu::BufferUsages::VERTEX
-            }
-        )
+    // The color comes from the theme
+    pub(super) fn from_planet(planet: &crate::sim::planet::Planet, color: [f32; 3]) -> Self {
+        Self::disc(planet.pos, planet.rad, color)
     }
 
-    pub(super) fn build_index_buffer(&self, device: &wgpu::Device) -> wgpu::Buffer {
-        device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents: bytemuck::cast_slice(self.indices.as_slice()),
-                usage: wgpu::BufferUsages::INDEX,
+    /// A single instance of one of the shapes
+    pub(super) fn shape(shape: Shape, pos: cgmath::Point2<f32>, rotation: f32, scale: f32, color: [f32; 3]) -> Self {
+        let mut m = Self::default();
+        m.instances[shape as usize].push(Instance {
+            position: [pos.x, pos.y],
+            rotation,
+            scale,
+            color
+        } );
+
+        m
+    }
+
+    /// A filled, 32-segment circle
+    pub(super) fn disc(pos: cgmath::Point2<f32>, rad: f32, color: [f32; 3]) -> Self {
+        Self::shape(Shape::Disc, pos, 0f32, rad, color)
+    }
+
+    /// Asteroids are too small to need a full disc
+    /// A diamond keeps belts of many bodies cheap to draw
+    pub(super) fn from_asteroid(asteroid: &crate::sim::planet::Planet, color: [f32; 3]) -> Self {
+        Self::shape(Shape::Diamond, asteroid.pos, 0f32, asteroid.rad, color)
+    }
+
+    /// Comets are drawn as an icy disc with a tail pointing away from the sun
+    /// Both fade to black as the comet leaves the inner system
+    pub(super) fn from_comet(comet: &crate::sim::planet::Planet, brightness: f32, color: [f32; 3]) -> Self {
+        let color = color.map(|c| c * brightness);
+
+        // Tail is a long triangle, darkening towards its tip
+        let crate::sim::planet::Planet { pos, rad, .. } = *comet;
+        let mut m = Self::disc(pos, rad, color);
+
+        let dist = (pos.x.powi(2) + pos.y.powi(2)).sqrt().max(f32::EPSILON);
+        let (dx, dy) = (pos.x / dist, pos.y / dist);
+        let len = rad * 12f32 * brightness;
+
+        m.vertices.append(&mut vec![
+            Vertex { position: [pos.x - dy * rad, pos.y + dx * rad, 0f32], color, alpha: 1f32 },
+            Vertex { position: [pos.x + dy * rad, pos.y - dx * rad, 0f32], color, alpha: 1f32 },
+            Vertex { position: [pos.x + dx * len, pos.y + dy * len, 0f32], color: [0f32; 3], alpha: 1f32 }
+        ] );
+        m.indices.append(&mut vec![0, 1, 2]);
+
+        m
+    }
+
+    /// A straight line of the given width, drawn as a quad
+    pub(super) fn line(a: cgmath::Point2<f32>, b: cgmath::Point2<f32>, width: f32, color: [f32; 3]) -> Self {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let len = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+        let (nx, ny) = (-dy / len * width * 0.5, dx / len * width * 0.5);
+
+        Self {
+            vertices: vec![
+                Vertex { position: [a.x + nx, a.y + ny, 0f32], color, alpha: 1f32 },
+                Vertex { position: [b.x + nx, b.y + ny, 0f32], color, alpha: 1f32 },
+                Vertex { position: [b.x - nx, b.y - ny, 0f32], color, alpha: 1f32 },
+                Vertex { position: [a.x - nx, a.y - ny, 0f32], color, alpha: 1f32 }
+            ],
+            indices: vec![0, 1, 2, 2, 3, 0],
+            ..Default::default()
+        }
+    }
+
+    /// Makes every free-form triangle translucent
+    /// Shapes are always opaque, so instances are left alone
+    pub(super) fn with_alpha(mut self, alpha: f32) -> Self {
+        self.vertices.iter_mut().for_each(|v| v.alpha = alpha);
+        self
+    }
+
+    /// A circular outline, used to mark the selection
+    pub(super) fn ring(pos: cgmath::Point2<f32>, rad: f32, width: f32, color: [f32; 3]) -> Self {
+        let points: Vec<_> = (0..32)
+            .map(|i| i as f32 / 32f32 * std::f32::consts::TAU)
+            .map(|a| cgmath::Point2::new(a.cos() * rad + pos.x, a.sin() * rad + pos.y))
+            .collect();
+
+        Self::outline(&points, width, color)
+    }
+
+    /// Part of a circular outline, running clockwise from `start` for `sweep` radians
+    /// Angles are measured clockwise from the top, like a ship's heading
+    pub(super) fn arc(pos: cgmath::Point2<f32>, rad: f32, start: f32, sweep: f32, width: f32, color: [f32; 3]) -> Self {
+        // Enough segments to look smooth, with at least one for the smallest arcs
+        let segments = (sweep / std::f32::consts::TAU * 32f32).ceil().max(1f32) as usize;
+        let points: Vec<_> = (0..=segments)
+            .map(|i| start + i as f32 / segments as f32 * sweep)
+            .map(|a| cgmath::Point2::new(a.sin() * rad + pos.x, a.cos() * rad + pos.y))
+            .collect();
+
+        Self::strip(&points, width, color, false)
+    }
+
+    /// The path of an orbit around its parent's current position
+    /// Elliptical orbits are traced out with the parent at one focus
+    pub(super) fn orbit(parent_pos: cgmath::Point2<f32>, orbit: &crate::sim::planet::Orbit, width: f32, color: [f32; 3]) -> Self {
+        let semi_latus = orbit.dist * (1f32 - orbit.ecc.powi(2));
+        let points: Vec<_> = (0..96)
+            .map(|i| i as f32 / 96f32 * std::f32::consts::TAU)
+            .map(|a| {
+                let r = semi_latus / (1f32 + orbit.ecc * (a - orbit.peri).cos());
+                cgmath::Point2::new(a.cos() * r + parent_pos.x, a.sin() * r + parent_pos.y)
+            } )
+            .collect();
+
+        Self::outline(&points, width, color)
+    }
+
+    /// A fading line through a ship's recent positions, oldest first
+    /// Fully transparent at its oldest end, `opacity` at its newest
+    pub(super) fn trail(points: &[cgmath::Point2<f32>], width: f32, color: [f32; 3], opacity: f32) -> Self {
+        let mut m = Self::strip(points, width, color, false);
+
+        let last = points.len().saturating_sub(1).max(1) as f32;
+        for (i, v) in m.vertices.iter_mut().enumerate() {
+            v.alpha = (i / 2) as f32 / last * opacity;
+        }
+
+        m
+    }
+
+    /// A thin closed loop through the given points
+    fn outline(points: &[cgmath::Point2<f32>], width: f32, color: [f32; 3]) -> Self {
+        Self::strip(points, width, color, true)
+    }
+
+    /// A thin line through the given points, optionally joining the last to the first
+    /// Each point is pushed out to either side, perpendicular to its neighbours
+    fn strip(points: &[cgmath::Point2<f32>], width: f32, color: [f32; 3], closed: bool) -> Self {
+        let count = points.len();
+
+        let mut m = Self::default();
+        for i in 0..count {
+            // Open strips use the point itself in place of a missing neighbour
+            let (prev, next) = match closed {
+                true => (points[(i + count - 1) % count], points[(i + 1) % count]),
+                false => (points[i.saturating_sub(1)], points[(i + 1).min(count - 1)])
+            };
+
+            let (dx, dy) = (next.x - prev.x, next.y - prev.y);
+            let len = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+            let (nx, ny) = (-dy / len * width * 0.5, dx / len * width * 0.5);
+
+            let p = points[i];
+            m.vertices.push(Vertex { position: [p.x + nx, p.y + ny, 0f32], color, alpha: 1f32 });
+            m.vertices.push(Vertex { position: [p.x - nx, p.y - ny, 0f32], color, alpha: 1f32 });
+
+            if !closed && i + 1 == count {
+                break;
             }
-        )
+
+            // Join this pair of points to the next
+            let (i, next) = (i as u32, ((i + 1) % count) as u32);
+            m.indices.extend([i * 2, i * 2 + 1, next * 2, next * 2, i * 2 + 1, next * 2 + 1]);
+        }
+
+        m
     }
-}
\ No newline at end of file
+
+    // The color is a render hint from the ship's behavior
+    pub(super) fn from_ship(ship: &crate::sim::ship::Ship, color: [f32; 3]) -> Self {
+        // The unit ship points up the y axis, while angles are measured clockwise from it
+        Self::shape(Shape::Ship, ship.pos, -ship.angle, Shape::SHIP_SIZE, color)
+    }
+
+    /// The tip of the ship, then the two corners at its back
+    pub(super) fn ship_points(ship: &crate::sim::ship::Ship) -> [cgmath::Point2<f32>; 3] {
+        let corner = |angle: f32| cgmath::Point2::new(
+            Shape::SHIP_SIZE * angle.sin() + ship.pos.x,
+            Shape::SHIP_SIZE * angle.cos() + ship.pos.y
+        );
+
+        [
+            ship.pos,
+            corner(ship.angle - Shape::SHIP_HALF_ANGLE),
+            corner(ship.angle + Shape::SHIP_HALF_ANGLE)
+        ]
+    }
+}
@@ -1,5 +1,18 @@
 mod mesh;
 mod camera;
+mod clock;
+mod history;
+mod font;
+mod inspector;
+mod text;
+mod hud;
+mod trails;
+mod buffer;
+mod msaa;
+mod theme;
+mod screensaver;
+mod raster;
+mod svg;
 
 use winit::{
     event,
@@ -10,28 +23,254 @@ use winit::{
 };
 use wgpu::util::DeviceExt;
 
+pub use mesh::Mesh;
+
+pub use theme::Theme;
+
+pub use camera::Camera;
+
+pub use raster::{Image, rasterize};
+
+pub use svg::{SvgConfig, write_svg};
+
 use mesh::{
-    Mesh,
-    Vertex
+    Vertex,
+    Shape,
+    ShapeVertex,
+    Instance
 };
 
-use camera::{
-    Camera,
-    CameraUniform
+use buffer::DynamicBuffer;
+
+use cgmath::Point2;
+
+use camera::CameraUniform;
+
+use clock::Clock;
+
+use history::History;
+
+use hud::Hud;
+
+use trails::Trails;
+
+use screensaver::Screensaver;
+
+use text::{
+    TextMesh,
+    TextRenderer
 };
 
-pub(crate) async fn run(mut sim: crate::sim::Sim) {
+/// A body or ship within the system being viewed
+/// Ships are kept by Ship::id, since their indices shift whenever one is removed
+#[derive(Copy, Clone, PartialEq)]
+enum Focus {
+    Planet(usize),
+    Ship(u64)
+}
+
+/// Either the map of the whole galaxy, or a single one of its systems
+#[derive(Copy, Clone, PartialEq)]
+enum View {
+    Galaxy { selected: usize },
+    System(usize)
+}
+
+impl View {
+    fn selected(&self) -> usize {
+        match *self {
+            View::Galaxy { selected } | View::System(selected) => selected
+        }
+    }
+}
+
+/// Options for the viewer window, separate from those of the sim
+pub struct UiConfig {
+    msaa_samples: u32,
+    theme: Theme,
+    screensaver: bool
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            msaa_samples: 4,
+            theme: Theme::default(),
+            screensaver: false
+        }
+    }
+}
+
+impl UiConfig {
+    /// Samples per pixel, 1 turns multisampling off
+    /// Steps down to the 4 that every adapter supports, or 1 if it can't multisample at all
+    pub fn with_msaa_samples(mut self, msaa_samples: u32) -> Self {
+        self.msaa_samples = msaa_samples;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Fullscreen with the cursor hidden, touring the galaxy on its own
+    /// Exits on any input
+    pub fn with_screensaver(mut self, screensaver: bool) -> Self {
+        self.screensaver = screensaver;
+        self
+    }
+}
+
+pub async fn run(mut galaxy: crate::sim::galaxy::Galaxy, config: UiConfig) {
     let event_loop = EventLoop::new();
-    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    let mut builder = WindowBuilder::new();
+    if config.screensaver {
+        builder = builder.with_fullscreen(Some(window::Fullscreen::Borderless(None)));
+    }
+
+    let window = builder.build(&event_loop).unwrap();
+    window.set_cursor_visible(!config.screensaver);
+
+    let mut view = View::System(0);
+    let mut inspected: Option<Focus> = None;
+
+    // The current speed is shown in the HUD and the window title
+    let mut clock = Clock::new();
+    let mut tick = 0;
+    window.set_title(&title(&clock, tick));
+
+    let mut history = History::new();
+    history.record(tick, &galaxy);
+
+    let mut screensaver = config.screensaver.then(Screensaver::new);
+
+    // A screensaver is just for watching
+    let mut hud = Hud::new();
+    hud.visible = screensaver.is_none();
+    let mut show_orbits = false;
+    let mut show_markers = true;
+
+    let mut trails = Trails::new();
+    let mut show_trails = true;
 
-    let mut state = State::new(&window).await;
+    let mut state = State::new(&window, &config).await;
     event_loop.run(move |event, _, control_flow| {
         match event {
             event::Event::RedrawRequested(w_id) if w_id == window.id() => {
-                sim.update();
+                let ticks = clock.ticks();
+                for _ in 0..ticks {
+                    galaxy.update();
+                    tick += 1;
+                    history.record(tick, &galaxy);
+                    hud.record(tick, &galaxy);
+                    trails.record(&galaxy);
+                }
+
+                hud.frame();
+
+                if ticks > 0 {
+                    window.set_title(&title(&clock, tick));
+                }
+
+                // Keep whatever is being followed in the centre of the screen
+                if let View::System(index) = view {
+                    let sim = &galaxy.systems[index];
+
+                    // Clicking selects whatever is under the cursor
+                    if let Some(click) = state.click.take() {
+                        use cgmath::MetricSpace;
+
+                        let scale = system_scale(sim);
+                        let pos = state.camera.unproject(click);
+
+                        // Within 8 pixels of the cursor, measured by unprojecting a point
+                        // that far away, so the camera's field of view is accounted for
+                        let offset = Point2::new(click.x, click.y + 16f32 / state.size.height as f32);
+                        let tolerance = pos.distance(state.camera.unproject(offset)) / scale;
+
+                        let pos = Point2::new(pos.x / scale, pos.y / scale);
+                        inspected = inspector::pick(sim, pos, tolerance);
+                    }
+
+                    inspected = inspected.filter(|&focus| focus_pos(sim, focus).is_some());
+                    state.follow = state.follow.filter(|&focus| {
+                        focus_pos(sim, focus).is_some()
+                    } );
+
+                    if let Some(pos) = state.follow.and_then(|focus| focus_pos(sim, focus)) {
+                        let scale = system_scale(sim);
+                        state.camera.pos = Point2::new(pos.x * scale, pos.y * scale);
+                    }
+                }
+
+                if let Some(screensaver) = &mut screensaver {
+                    screensaver.update(&galaxy, &mut view, &mut state.camera);
+                }
+
+                let mesh = match view {
+                    View::Galaxy { selected } => {
+                        build_galaxy_mesh(&galaxy, selected, &config.theme)
+                    },
+                    View::System(index) => {
+                        let sim = &galaxy.systems[index];
+
+                        // Orbits go underneath everything else
+                        let mut mesh = match show_orbits {
+                            true => build_orbit_mesh(sim, 0.002f32 * state.camera.zoom),
+                            false => Mesh::default()
+                        };
+
+                        // Then trails, so that ships are drawn over their own
+                        if show_trails {
+                            let scale = system_scale(sim);
+                            let width = 0.003f32 * state.camera.zoom / scale;
+                            combine_meshes(&mut mesh, trails.build_mesh(sim, index, width, &config.theme), scale);
+                        }
+
+                        combine_meshes(&mut mesh, build_mesh(sim, &config.theme), 1f32);
+
+                        if show_markers {
+                            let width = 0.003f32 * state.camera.zoom;
+                            combine_meshes(&mut mesh, build_marker_mesh(sim, width, &config.theme), 1f32);
+                        }
+
+                        // Outline the selection
+                        if let Some(focus) = inspected {
+                            let scale = system_scale(sim);
+                            let pos = focus_pos(sim, focus).unwrap();
+                            let rad = match focus {
+                                Focus::Planet(pl_index) => sim.system[pl_index].rad,
+                                Focus::Ship(..) => 0.05f32
+                            };
+
+                            combine_meshes(&mut mesh, Mesh::ring(
+                                Point2::new(pos.x * scale, pos.y * scale),
+                                rad * scale * 1.3f32,
+                                0.004f32 * state.camera.zoom,
+                                [1f32, 1f32, 1f32]
+                            ).with_alpha(0.6f32), 1f32);
+                        }
+
+                        mesh
+                    }
+                };
+
+                // The HUD sits in the top left, the inspector in the top right
+                let mut text = TextMesh::new(state.size);
+                if hud.visible {
+                    let hud_text = hud.text(tick, &clock, &galaxy, view.selected());
+                    text.panel(16f32, 16f32, &hud_text, [1f32, 1f32, 1f32]);
+                }
+
+                if let (View::System(index), Some(focus)) = (view, inspected) {
+                    let description = inspector::describe(&galaxy.systems[index], focus);
+                    let (width, _) = TextMesh::panel_size(&description);
+                    let x = state.size.width as f32 - width - 16f32;
+                    text.panel(x, 16f32, &description, [1f32, 1f32, 1f32]);
+                }
 
-                let mesh = build_mesh(&sim);
-                state.update(&mesh);
+                state.update(&mesh, &text);
 
                 match state.render() {
                     Ok(..) => {  },
@@ -48,7 +287,13 @@ pub(crate) async fn run(mut sim: crate::sim::Sim) {
             event::Event::WindowEvent {
                 ref event,
                 window_id,
-            } if window_id == window.id() => if !state.input(event) {
+            } if window_id == window.id() && screensaver.as_mut().is_some_and(|s| s.wakes(event)) => {
+                *control_flow = ControlFlow::Exit
+            },
+            event::Event::WindowEvent {
+                ref event,
+                window_id,
+            } if window_id == window.id() && !state.input(event) => {
                 match event {
                     // Handle close behavior
                     WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
@@ -68,6 +313,51 @@ pub(crate) async fn run(mut sim: crate::sim::Sim) {
                     WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
                         state.resize(**new_inner_size) 
                     },
+                    WindowEvent::KeyboardInput {
+                        input:
+                        event::KeyboardInput {
+                                state: event::ElementState::Pressed,
+                                virtual_keycode: Some(keycode),
+                                ..
+                            },
+                        ..
+                    } => if clock.input(*keycode) {
+                        window.set_title(&title(&clock, tick));
+                    } else if let Some(ticks) = rewind_ticks(*keycode) {
+                        if let Some(past) = history.rewind(tick.saturating_sub(ticks)) {
+                            (tick, galaxy) = past;
+                            trails.clear();
+                            clock.pause();
+                            window.set_title(&title(&clock, tick));
+                        }
+                    } else if *keycode == event::VirtualKeyCode::H {
+                        hud.visible = !hud.visible;
+                    } else if *keycode == event::VirtualKeyCode::O {
+                        show_orbits = !show_orbits;
+                    } else if *keycode == event::VirtualKeyCode::M {
+                        show_markers = !show_markers;
+                    } else if *keycode == event::VirtualKeyCode::T {
+                        show_trails = !show_trails;
+                    } else if let (View::System(index), event::VirtualKeyCode::X) = (view, *keycode) {
+                        export_svg(&galaxy.systems[index], tick, show_orbits, &config.theme);
+                    } else if let (View::System(index), event::VirtualKeyCode::F) = (view, *keycode) {
+                        // Follow the selection, or whatever is nearest the centre
+                        state.follow = match state.follow {
+                            Some(..) => None,
+                            None => inspected.or_else(|| {
+                                nearest_focus(&galaxy.systems[index], state.camera.pos)
+                            } )
+                        };
+                    } else {
+                        // Nothing in one system can be followed into another
+                        let next = switch_view(view, *keycode, galaxy.systems.len());
+                        if next != view {
+                            state.follow = None;
+                            inspected = None;
+                        }
+
+                        view = next;
+                    },
                     _ => {}
                 }
             },
@@ -76,49 +366,293 @@ pub(crate) async fn run(mut sim: crate::sim::Sim) {
     });
 }
 
-fn build_mesh(sim: &crate::sim::Sim) -> Mesh {
-    fn combine_meshes(m1: &mut Mesh, mut m2: Mesh, scale: f32) {
-        let offset = m1.vertices.len();
-        m2.vertices.iter_mut().for_each(|v| { 
-            v.position[0] *= scale; 
-            v.position[1] *= scale; } );
-        m1.vertices.append(&mut m2.vertices);
-        m2.indices.iter_mut().for_each(|i| *i += offset as u16);
-        m1.indices.append(&mut m2.indices);
+// Repeats what the HUD shows, so the speed stays visible while the HUD is hidden
+fn title(clock: &Clock, tick: usize) -> String {
+    format!("solarsim ({}, tick {})", clock.label(), tick)
+}
+
+/// Saves the system to an SVG in the working directory, named after the tick
+/// Orbits are included if they're shown
+fn export_svg(sim: &crate::sim::Sim, tick: usize, orbits: bool, theme: &Theme) {
+    let path = format!("solarsim-{}.svg", tick);
+    let config = SvgConfig::default()
+        .with_orbits(orbits)
+        .with_labels(true);
+
+    let written = std::fs::File::create(&path).and_then(|file| {
+        use std::io::Write;
+
+        let mut file = std::io::BufWriter::new(file);
+        write_svg(sim, theme, &config, &mut file)?;
+        file.flush()
+    } );
+
+    match written {
+        Ok(..) => println!("saved {}", path),
+        Err(e) => eprintln!("{}: {}", path, e)
+    }
+}
+
+/// Comma steps back a single tick, left bracket steps back 5 seconds
+fn rewind_ticks(keycode: event::VirtualKeyCode) -> Option<usize> {
+    match keycode {
+        event::VirtualKeyCode::Comma => Some(1),
+        event::VirtualKeyCode::LBracket => Some(300),
+        _ => None
+    }
+}
+
+/// Current position of a focused body or ship, if it still exists
+fn focus_pos(sim: &crate::sim::Sim, focus: Focus) -> Option<Point2<f32>> {
+    match focus {
+        Focus::Planet(pl_index) => sim.system.get(pl_index).map(|pl| pl.pos),
+        Focus::Ship(id) => ship_index(sim, id).map(|ship_index| sim.ships[ship_index].pos)
+    }
+}
+
+/// Where the ship with the given Ship::id currently is in the sim's list, if it's still there
+fn ship_index(sim: &crate::sim::Sim, id: u64) -> Option<usize> {
+    sim.ships.iter().position(|ship| ship.id == id)
+}
+
+/// The body or ship closest to the given point in mesh coordinates
+fn nearest_focus(sim: &crate::sim::Sim, pos: Point2<f32>) -> Option<Focus> {
+    use cgmath::MetricSpace;
+    use crate::sim::planet::BodyKind;
+
+    let scale = system_scale(sim);
+    let pos = Point2::new(pos.x / scale, pos.y / scale);
+
+    let planets = sim.system.iter()
+        .enumerate()
+        .filter(|(_, pl)| pl.kind != BodyKind::Barycenter)
+        .map(|(pl_index, pl)| (Focus::Planet(pl_index), pl.pos));
+    let ships = sim.ships.iter()
+        .map(|ship| (Focus::Ship(ship.id), ship.pos));
+
+    planets.chain(ships)
+        .min_by(|(_, a), (_, b)| {
+            pos.distance2(*a).partial_cmp(&pos.distance2(*b)).unwrap_or(std::cmp::Ordering::Equal)
+        } )
+        .map(|(focus, _)| focus)
+}
+
+/// G toggles the galaxy map, Tab and the number keys pick a system
+fn switch_view(view: View, keycode: event::VirtualKeyCode, system_count: usize) -> View {
+    use event::VirtualKeyCode::*;
+
+    let selected = view.selected();
+    let selected = match keycode {
+        Tab => (selected + 1) % system_count,
+        Key1 | Key2 | Key3 | Key4 | Key5 | Key6 | Key7 | Key8 | Key9 => {
+            let index = keycode as usize - Key1 as usize;
+            if index < system_count { index } else { selected }
+        },
+        _ => selected
+    };
+
+    match (view, keycode) {
+        (View::Galaxy { .. }, G) => View::System(selected),
+        (View::System(..), G) => View::Galaxy { selected },
+        (View::Galaxy { .. }, _) => View::Galaxy { selected },
+        (View::System(..), _) => View::System(selected)
     }
+}
+
+fn combine_meshes(m1: &mut Mesh, mut m2: Mesh, scale: f32) {
+    let offset = m1.vertices.len();
+    m2.vertices.iter_mut().for_each(|v| { 
+        v.position[0] *= scale; 
+        v.position[1] *= scale; } );
+    m1.vertices.append(&mut m2.vertices);
+    m2.indices.iter_mut().for_each(|i| *i += offset as u32);
+    m1.indices.append(&mut m2.indices);
+
+    for (instances, mut other) in m1.instances.iter_mut().zip(m2.instances) {
+        other.iter_mut().for_each(|instance| {
+            instance.position[0] *= scale;
+            instance.position[1] *= scale;
+            instance.scale *= scale; } );
+        instances.append(&mut other);
+    }
+}
+
+/// Systems are drawn as their star, sized by the number of ships they hold
+/// Jump gate links are drawn as lines between them
+fn build_galaxy_mesh(galaxy: &crate::sim::galaxy::Galaxy, selected: usize, theme: &Theme) -> Mesh {
+    use crate::sim::planet::BodyKind;
 
     let mut m = Mesh::default();
-    let scale = (sim.system_rad.powf(2f32) * 2f32).sqrt().recip();
+    let scale = 0.35f32;
 
-    for planet in sim.system.iter() {
+    for &(a, b) in galaxy.links.iter() {
         combine_meshes(
             &mut m,
-            Mesh::from_planet(planet),
+            Mesh::line(
+                galaxy.positions[a], 
+                galaxy.positions[b], 
+                0.01f32, 
+                [0.3f32, 0.3f32, 0.3f32]),
             scale
         );
     }
 
+    for (index, sim) in galaxy.systems.iter().enumerate() {
+        let pos = galaxy.positions[index];
+        let rad = 0.04f32 + 0.004f32 * (sim.ships.len() as f32).sqrt();
+
+        // The selected system is outlined
+        if index == selected {
+            combine_meshes(
+                &mut m, 
+                Mesh::disc(pos, rad * 1.3, [1f32, 1f32, 1f32]), 
+                scale
+            );
+        }
+
+        // Use the color of the system's first star
+        let star = sim.system.iter()
+            .find(|pl| pl.kind == BodyKind::Star)
+            .unwrap();
+        let color = theme.planet_color(star);
+
+        combine_meshes(&mut m, Mesh::disc(pos, rad, color), scale);
+    }
+
+    m
+}
+
+/// Factor from sim coordinates to mesh coordinates
+/// Fits the whole system into view at the default zoom
+fn system_scale(sim: &crate::sim::Sim) -> f32 {
+    (sim.system_rad.powf(2f32) * 2f32).sqrt().recip()
+}
+
+/// Thin rings tracing each body's orbit around its parent
+/// Asteroids are left out, their belts would just be a solid band
+/// Width is in mesh coordinates, so it can be kept constant on screen
+fn build_orbit_mesh(sim: &crate::sim::Sim, width: f32) -> Mesh {
+    use crate::sim::planet::BodyKind;
+
+    let mut m = Mesh::default();
+    let scale = system_scale(sim);
+
+    for planet in sim.system.iter() {
+        if planet.kind == BodyKind::Asteroid {
+            continue;
+        }
+
+        // Moons follow their parent, since it's the parent's current position
+        if let Some(orbit) = &planet.orbit {
+            let parent_pos = sim.system[orbit.parent_index].pos;
+            combine_meshes(
+                &mut m,
+                Mesh::orbit(parent_pos, orbit, width / scale, [0.25f32, 0.25f32, 0.25f32]),
+                scale
+            );
+        }
+    }
+
+    m
+}
+
+/// Marks what each planet is for, drawn just outside of it
+/// Stations get a gauge that fills up as their stock nears the cost of a ship,
+/// and ore planets a diamond on each side, bigger the richer they are
+fn build_marker_mesh(sim: &crate::sim::Sim, width: f32, theme: &Theme) -> Mesh {
+    use crate::sim::planet::{BodyKind, PlanetFeature};
+    use std::f32::consts::TAU;
+
+    let mut m = Mesh::default();
+    let scale = system_scale(sim);
+
+    // Asteroids and comets are too small and too many to be worth marking
+    let planets = sim.system.iter().filter(|planet| planet.kind == BodyKind::Planet);
+
+    for planet in planets {
+        let pos = Point2::new(planet.pos.x * scale, planet.pos.y * scale);
+        let rad = planet.rad * scale * 1.6f32;
+        let color = theme.planet_color(planet);
+
+        match planet.feat {
+            Some(PlanetFeature::Station { stock }) => {
+                // Stations launch a ship as soon as they have more than it costs
+                let fill = (stock as f32 / (sim.ship_cost() + 1) as f32).min(1f32);
+
+                combine_meshes(&mut m, Mesh::ring(pos, rad, width, color).with_alpha(0.25f32), 1f32);
+                if fill > 0f32 {
+                    combine_meshes(&mut m, Mesh::arc(pos, rad, 0f32, fill * TAU, width * 2f32, color), 1f32);
+                }
+            },
+            Some(PlanetFeature::Ore { richness }) => {
+                let size = width * (1f32 + richness.min(2f32));
+                for i in 0..4 {
+                    let a = i as f32 / 4f32 * TAU;
+                    let marker = Point2::new(pos.x + a.sin() * rad, pos.y + a.cos() * rad);
+                    combine_meshes(&mut m, Mesh::shape(Shape::Diamond, marker, 0f32, size, color), 1f32);
+                }
+            },
+            _ => {  }
+        }
+    }
+
+    m
+}
+
+/// Triangles for every body and ship in the system, in mesh coordinates
+/// Sim positions are scaled to fit the system within -1..1, the camera is applied
+/// afterwards, by the shader on screen or by rasterize
+pub fn build_mesh(sim: &crate::sim::Sim, theme: &Theme) -> Mesh {
+    let mut m = Mesh::default();
+    let scale = system_scale(sim);
+
+    for (pl_index, planet) in sim.system.iter().enumerate() {
+        use crate::sim::planet::BodyKind;
+
+        let mesh = match planet.kind {
+            BodyKind::Barycenter => continue,
+            BodyKind::Asteroid => Mesh::from_asteroid(planet, theme.planet_color(planet)),
+            BodyKind::Comet => {
+                // Comets far from the sun aren't drawn at all
+                let brightness = sim.comet_brightness(pl_index);
+                if brightness == 0f32 {
+                    continue;
+                }
+
+                Mesh::from_comet(planet, brightness, theme.planet_color(planet))
+            },
+            _ => Mesh::from_planet(planet, theme.planet_color(planet))
+        };
+
+        combine_meshes(&mut m, mesh, scale);
+    }
+
+    let ship_colors = theme.ship_colors(sim.jobs());
     for (ship_index, ship) in sim.ships.iter().enumerate() {
+        let color = ship_colors.get(sim, ship_index);
         combine_meshes(
             &mut m,
-            Mesh::from_ship(ship),
+            Mesh::from_ship(ship, color),
             scale
         );
 
         if let crate::sim::ship::ShipGoal::Hunt { prey, .. } = ship.goal {
             if sim.pirate_in_range(ship_index) {
-                let prey_mesh = Mesh::from_ship(&sim.ships[prey]);
-                let ship_mesh = Mesh::from_ship(ship);
+                // A beam from the pirate's tip to the back of its prey
+                let [tip, ..] = Mesh::ship_points(ship);
+                let [_, left, right] = Mesh::ship_points(&sim.ships[prey]);
+                let black = [0f32; 3];
 
                 combine_meshes(
                     &mut m,
                     Mesh {
                         vertices: vec![
-                            ship_mesh.vertices[0],
-                            prey_mesh.vertices[1],
-                            prey_mesh.vertices[2]
+                            Vertex { position: [tip.x, tip.y, 0f32], color, alpha: 1f32 },
+                            Vertex { position: [left.x, left.y, 0f32], color: black, alpha: 1f32 },
+                            Vertex { position: [right.x, right.y, 0f32], color: black, alpha: 1f32 }
                         ],
-                        indices: vec![0, 1, 2]
+                        indices: vec![0, 1, 2],
+                        ..Default::default()
                     },
                     scale
                 );
@@ -135,18 +669,28 @@ struct State {
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
+    samples: u32,
+    msaa_target: Option<wgpu::TextureView>,
+    vertex_buffer: DynamicBuffer,
+    index_buffer: DynamicBuffer,
     index_count: u32,
+    shapes: Vec<ShapeBuffers>,
     camera: Camera,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
-    render_pipeline: wgpu::RenderPipeline
+    render_pipeline: wgpu::RenderPipeline,
+    instance_pipeline: wgpu::RenderPipeline,
+    text_renderer: TextRenderer,
+    cursor: Point2<f32>,
+    pressed_at: Option<Point2<f32>>,
+    dragging: bool,
+    click: Option<Point2<f32>>,
+    follow: Option<Focus>
 }
 
 impl State {
-    async fn new(window: &window::Window) -> Self {
+    async fn new(window: &window::Window, ui_config: &UiConfig) -> Self {
         let size = window.inner_size();
 
         let instance = wgpu::Instance::new(wgpu::Backends::all());
@@ -161,7 +705,7 @@ impl State {
                 compatible_surface: Some(&surface),
                 force_fallback_adapter: false
             },
-        ).await.unwrap();
+        ).await.expect("no graphics adapter found, `solarsim render-frame` can draw without one");
 
         let (device, queue) = adapter.request_device(
             &wgpu::DeviceDescriptor {
@@ -188,29 +732,25 @@ impl State {
 
         surface.configure(&device, &config);
 
-        let vertex_buffer = device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents: &[],
-                usage: wgpu::BufferUsages::VERTEX
-            }
-        );
+        let samples = msaa::sample_count(&adapter, config.format, ui_config.msaa_samples);
 
-        let index_buffer = device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents: &[],
-                usage: wgpu::BufferUsages::INDEX,
-            }
-        );
+        let msaa_target = msaa::create_target(&device, &config, samples);
 
+        // Free-form triangles are rewritten every frame
+        let vertex_buffer = DynamicBuffer::new(&device, wgpu::BufferUsages::VERTEX);
+        let index_buffer = DynamicBuffer::new(&device, wgpu::BufferUsages::INDEX);
         let index_count = 0u32;
 
-        let camera = Camera {
-            pos: (0f32, 0f32).into(),
-            zoom: 1f32,
-            aspect: config.width as f32 / config.height as f32
-        };
+        // While shapes never change, only their instances do
+        let shapes = Shape::ALL.iter()
+            .map(|&shape| ShapeBuffers::new(&device, shape))
+            .collect();
+
+        let camera = Camera::new(
+            (0f32, 0f32).into(),
+            1f32,
+            config.width as f32 / config.height as f32
+        );
 
         let mut camera_uniform = CameraUniform::new();
         camera_uniform.update_projection(&camera);
@@ -270,63 +810,62 @@ impl State {
             }
         );
 
-        let render_pipeline = device.create_render_pipeline(
-            &wgpu::RenderPipelineDescriptor {
-                label: None,
-                layout: Some(&render_pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vs_main",
-                    buffers: &[
-                        Vertex::description()
-                    ]
-                },
-                fragment: Some(
-                    wgpu::FragmentState {
-                        module: &shader,
-                        entry_point: "fs_main",
-                        targets: &[
-                            Some(wgpu::ColorTargetState {
-                                format: config.format,
-                                blend: Some(wgpu::BlendState::REPLACE),
-                                write_mask: wgpu::ColorWrites::ALL
-                            } )
-                        ],
-                    }
-                ),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Cw,
-                    cull_mode: None, //Some(wgpu::Face::Back)
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None
-            }
+        // Free-form triangles hold the translucent overlays, trails and selection rings
+        // Bodies and ships are always opaque
+        let render_pipeline = create_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            "vs_main",
+            &[Vertex::description()],
+            wgpu::ColorTargetState {
+                format: config.format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL
+            },
+            samples
         );
 
+        let instance_pipeline = create_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            "vs_instance",
+            &[ShapeVertex::description(), Instance::description()],
+            wgpu::ColorTargetState {
+                format: config.format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL
+            },
+            samples
+        );
+
+        let text_renderer = TextRenderer::new(&device, &queue, config.format, samples);
+
         Self {
             size,
             surface,
             device,
             queue,
             config,
+            samples,
+            msaa_target,
             vertex_buffer,
             index_buffer,
             index_count,
+            shapes,
             camera,
             camera_uniform,
             camera_buffer,
             camera_bind_group,
-            render_pipeline
+            render_pipeline,
+            instance_pipeline,
+            text_renderer,
+            cursor: (0f32, 0f32).into(),
+            pressed_at: None,
+            dragging: false,
+            click: None,
+            follow: None
         }
     }
 
@@ -336,6 +875,10 @@ impl State {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
+            self.msaa_target = msaa::create_target(&self.device, &self.config, self.samples);
+
+            // Otherwise the view stretches along with the window
+            self.camera.aspect = new_size.width as f32 / new_size.height as f32;
         }
     }
 
@@ -343,18 +886,78 @@ impl State {
         self.resize(self.size);
     }
 
+    /// Handles panning and zooming
+    /// Moving the camera by hand stops it from following anything
     fn input(&mut self, event: &WindowEvent) -> bool {
         let mut processed: bool = true;
         use WindowEvent::*;
         match event {
-            MouseWheel { 
-                delta: 
-                    event::MouseScrollDelta::LineDelta(.., line_delta), 
-                    .. 
+            MouseWheel { delta, .. } => {
+                // Trackpads report pixels, mice report lines
+                let lines = match delta {
+                    event::MouseScrollDelta::LineDelta(.., line_delta) => *line_delta,
+                    event::MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 50f32
+                };
+
+                self.camera.zoom_at(self.cursor, 0.9f32.powf(lines));
+            },
+            CursorMoved { position, .. } => {
+                let cursor = Point2::new(
+                    position.x as f32 / self.size.width as f32 * 2f32 - 1f32,
+                    1f32 - position.y as f32 / self.size.height as f32 * 2f32
+                );
+
+                // Small movements while clicking don't count as a drag
+                if let Some(pressed_at) = self.pressed_at {
+                    use cgmath::MetricSpace;
+                    if pressed_at.distance(cursor) > 0.01f32 {
+                        self.dragging = true;
+                    }
+                }
+
+                if self.dragging {
+                    self.camera.drag(self.cursor, cursor);
+                    self.follow = None;
+                }
+
+                self.cursor = cursor;
+            },
+            MouseInput { state, button: event::MouseButton::Left, .. } => {
+                match state {
+                    event::ElementState::Pressed => {
+                        self.pressed_at = Some(self.cursor);
+                    },
+                    event::ElementState::Released => {
+                        if !self.dragging {
+                            self.click = Some(self.cursor);
+                        }
+
+                        self.pressed_at = None;
+                        self.dragging = false;
+                    }
+                }
+            },
+            KeyboardInput {
+                input: event::KeyboardInput {
+                    state: event::ElementState::Pressed,
+                    virtual_keycode: Some(keycode),
+                    ..
+                },
+                ..
             } => {
-                let zoom = self.camera.zoom + line_delta * -0.1f32;
-                let zoom = zoom.clamp(0.5f32, 2f32);
-                self.camera.zoom = zoom;  
+                // Arrow keys move a tenth of the screen at a time
+                use event::VirtualKeyCode::*;
+                let step = match keycode {
+                    Left => (-0.2f32, 0f32),
+                    Right => (0.2f32, 0f32),
+                    Up => (0f32, 0.2f32),
+                    Down => (0f32, -0.2f32),
+                    _ => return false
+                };
+
+                let to = Point2::new(-step.0, -step.1);
+                self.camera.drag((0f32, 0f32).into(), to);
+                self.follow = None;
             },
             _ => { processed = false }
         }
@@ -362,11 +965,18 @@ impl State {
         processed
     }
 
-    fn update(&mut self, mesh: &Mesh) {
+    fn update(&mut self, mesh: &Mesh, text: &TextMesh) {
         self.index_count = mesh.indices.len() as u32;
 
-        self.vertex_buffer = mesh.build_vertex_buffer(&self.device);
-        self.index_buffer = mesh.build_index_buffer(&self.device);
+        self.vertex_buffer.write(&self.device, &self.queue, bytemuck::cast_slice(&mesh.vertices));
+        self.index_buffer.write(&self.device, &self.queue, bytemuck::cast_slice(&mesh.indices));
+
+        for (shape, instances) in self.shapes.iter_mut().zip(mesh.instances.iter()) {
+            shape.instance_count = instances.len() as u32;
+            shape.instance_buffer.write(&self.device, &self.queue, bytemuck::cast_slice(instances));
+        }
+
+        self.text_renderer.update(&self.device, &self.queue, text);
 
         self.camera_uniform.update_projection(&self.camera);
         self.queue.write_buffer(
@@ -395,8 +1005,9 @@ impl State {
                     color_attachments: &[
                         Some(
                             wgpu::RenderPassColorAttachment {
-                                view: &view,
-                                resolve_target: None,
+                                // Multisampled frames are resolved onto the surface
+                                view: self.msaa_target.as_ref().unwrap_or(&view),
+                                resolve_target: self.msaa_target.as_ref().map(|_| &view),
                                 ops: wgpu::Operations {
                                     load: wgpu::LoadOp::Clear(
                                         wgpu::Color::BLACK
@@ -410,20 +1021,39 @@ impl State {
                 }
             );
 
-            render_pass.set_pipeline(&self.render_pipeline);
             render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
 
-            render_pass.set_vertex_buffer(
-                0, 
-                self.vertex_buffer.slice(..)
-            );
+            if self.index_count > 0 {
+                render_pass.set_pipeline(&self.render_pipeline);
 
-            render_pass.set_index_buffer(
-                self.index_buffer.slice(..), 
-                wgpu::IndexFormat::Uint16
-            );
+                render_pass.set_vertex_buffer(
+                    0, 
+                    self.vertex_buffer.slice()
+                );
 
-            render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+                render_pass.set_index_buffer(
+                    self.index_buffer.slice(), 
+                    wgpu::IndexFormat::Uint32
+                );
+
+                render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+            }
+
+            // Bodies and ships go over the top of orbits and trails
+            render_pass.set_pipeline(&self.instance_pipeline);
+            for shape in self.shapes.iter().filter(|shape| shape.instance_count > 0) {
+                render_pass.set_vertex_buffer(0, shape.vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, shape.instance_buffer.slice());
+                render_pass.set_index_buffer(
+                    shape.index_buffer.slice(..), 
+                    wgpu::IndexFormat::Uint32
+                );
+
+                render_pass.draw_indexed(0..shape.index_count, 0, 0..shape.instance_count);
+            }
+
+            // Text goes over the top of the scene
+            self.text_renderer.draw(&mut render_pass);
         }
     
         self.queue.submit(
@@ -434,4 +1064,110 @@ impl State {
     
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Static mesh of a single Shape, and the instances drawn with it this frame
+struct ShapeBuffers {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    instance_buffer: DynamicBuffer,
+    instance_count: u32
+}
+
+impl ShapeBuffers {
+    fn new(device: &wgpu::Device, shape: Shape) -> Self {
+        let (vertices, indices) = shape.outline();
+
+        let vertex_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX
+            }
+        );
+
+        let index_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX
+            }
+        );
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            instance_buffer: DynamicBuffer::new(device, wgpu::BufferUsages::VERTEX),
+            instance_count: 0
+        }
+    }
+}
+
+/// Both scene pipelines share everything but their vertex stage and blending
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    entry_point: &str,
+    buffers: &[wgpu::VertexBufferLayout],
+    target: wgpu::ColorTargetState,
+    samples: u32
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(
+        &wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point,
+                buffers
+            },
+            fragment: Some(
+                wgpu::FragmentState {
+                    module: shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(target)],
+                }
+            ),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: None, //Some(wgpu::Face::Back)
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{Sim, SimConfig};
+
+    #[test]
+    fn followed_ship_survives_earlier_removals() {
+        let mut sim = Sim::new(SimConfig::default().with_seed(1));
+        let followed = sim.ships[5].clone();
+        let focus = Focus::Ship(followed.id);
+
+        // Removing a ship shifts every later one down a place
+        sim.ships.remove(0);
+        assert_eq!(focus_pos(&sim, focus), Some(followed.pos));
+
+        // Once it's gone there's nothing left to follow
+        sim.ships.retain(|ship| ship.id != followed.id);
+        assert_eq!(focus_pos(&sim, focus), None);
+    }
+}
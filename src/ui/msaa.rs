@@ -0,0 +1,61 @@
+/// The requested sample count, as far as it can be confirmed to work with the format
+/// Formats that can be multisampled and resolved are guaranteed 4 samples,
+/// but wgpu has no way to ask about more, so higher counts step down to 4
+/// Formats that can't be multisampled only get 1
+pub(super) fn sample_count(
+    adapter: &wgpu::Adapter,
+    format: wgpu::TextureFormat,
+    requested: u32
+) -> u32 {
+    const GUARANTEED: u32 = 4;
+
+    let flags = adapter.get_texture_format_features(format).flags;
+    let msaa = wgpu::TextureFormatFeatureFlags::MULTISAMPLE
+        | wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE;
+
+    let samples = match flags.contains(msaa) {
+        true => requested.min(GUARANTEED),
+        false => 1
+    };
+
+    if samples < requested {
+        eprintln!("{}x multisampling isn't known to be supported, using {}x", requested, samples);
+    }
+
+    samples
+}
+
+/// The multisampled target that's resolved onto the surface each frame
+/// There's nothing to resolve with a single sample, so then there's no target at all
+pub(super) fn create_target(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    samples: u32
+) -> Option<wgpu::TextureView> {
+    (samples > 1).then(|| {
+        let size = winit::dpi::PhysicalSize::new(config.width, config.height);
+        create_texture(device, config.format, size, samples)
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    } )
+}
+
+fn create_texture(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    size: winit::dpi::PhysicalSize<u32>,
+    samples: u32
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1
+        },
+        mip_level_count: 1,
+        sample_count: samples,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+    } )
+}
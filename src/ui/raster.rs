@@ -0,0 +1,215 @@
+use std::io;
+
+use cgmath::{Matrix4, Vector4};
+
+use super::{
+    camera::Camera,
+    mesh::{Mesh, Shape, Vertex}
+};
+
+/// A frame drawn on the CPU, for machines without a GPU
+/// Pixels are kept in linear color, like the GPU's blending, and only encoded as sRGB on output
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 3]>
+}
+
+impl Image {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0f32; 3]; width as usize * height as usize]
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Rows of 8-bit sRGB pixels, top to bottom
+    pub fn to_rgb8(&self) -> Vec<u8> {
+        self.pixels.iter()
+            .flat_map(|pixel| pixel.map(to_srgb8))
+            .collect()
+    }
+
+    pub fn write_png(&self, w: impl io::Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(w, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgb8())?;
+        writer.finish()?;
+
+        Ok(())
+    }
+
+    // Position of a pixel in `pixels`, computed in usize so large images can't overflow
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    // Averages each square of factor x factor pixels into one
+    fn downsample(&self, factor: u32) -> Self {
+        let mut image = Self::new(self.width / factor, self.height / factor);
+        let weight = ((factor * factor) as f32).recip();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = self.pixels[self.index(x, y)];
+                let index = image.index(x / factor, y / factor);
+                let target = &mut image.pixels[index];
+                for (t, p) in target.iter_mut().zip(pixel) {
+                    *t += p * weight;
+                }
+            }
+        }
+
+        image
+    }
+}
+
+/// Draws the mesh as the GPU would, without needing one
+/// Free-form triangles are blended first, then every shape instance is drawn opaque on top,
+/// with each pixel averaging a 2x2 grid of samples to smooth edges like 4x MSAA
+pub fn rasterize(mesh: &Mesh, camera: &Camera, width: u32, height: u32) -> Image {
+    const SUPERSAMPLE: u32 = 2;
+
+    let mut target = Target {
+        image: Image::new(width * SUPERSAMPLE, height * SUPERSAMPLE),
+        projection: camera.build_view_projection_matrix()
+    };
+
+    for triangle in mesh.indices.chunks_exact(3) {
+        target.triangle(triangle.iter().map(|&i| mesh.vertices[i as usize]), true);
+    }
+
+    // Instances are flattened into triangles of their shape's unit mesh, as in vs_instance
+    for (shape, instances) in Shape::ALL.into_iter().zip(&mesh.instances) {
+        let (outline, indices) = shape.outline();
+        for instance in instances {
+            let (s, c) = instance.rotation.sin_cos();
+            let place = |i: &u32| {
+                let vertex = outline[*i as usize];
+                let [x, y] = vertex.position;
+                Vertex {
+                    position: [
+                        (c * x - s * y) * instance.scale + instance.position[0],
+                        (s * x + c * y) * instance.scale + instance.position[1],
+                        0f32
+                    ],
+                    color: instance.color.map(|c| c * vertex.shade),
+                    alpha: 1f32
+                }
+            };
+
+            for triangle in indices.chunks_exact(3) {
+                target.triangle(triangle.iter().map(place), false);
+            }
+        }
+    }
+
+    target.image.downsample(SUPERSAMPLE)
+}
+
+struct Target {
+    image: Image,
+    projection: Matrix4<f32>
+}
+
+impl Target {
+    // Samples are taken at pixel centers, and a sample exactly on an edge belongs to
+    // only one of the triangles sharing it, so translucent strips don't show their seams
+    fn triangle(&mut self, vertices: impl Iterator<Item = Vertex>, blend: bool) {
+        let mut points = [[0f32; 2]; 3];
+        let mut colors = [[0f32; 4]; 3];
+        for (i, vertex) in vertices.take(3).enumerate() {
+            let [x, y, z] = vertex.position;
+            let clip = self.projection * Vector4::new(x, y, z, 1f32);
+            if clip.w <= 0f32 {
+                return;
+            }
+
+            // Normalized device coords to pixels, with y pointing down
+            points[i] = [
+                (clip.x / clip.w + 1f32) * 0.5 * self.image.width as f32,
+                (1f32 - clip.y / clip.w) * 0.5 * self.image.height as f32
+            ];
+
+            let [r, g, b] = vertex.color;
+            colors[i] = [r, g, b, vertex.alpha];
+        }
+
+        // Both windings are drawn, like the pipelines' lack of culling
+        let mut area = edge(points[0], points[1], points[2]);
+        if area == 0f32 {
+            return;
+        } else if area < 0f32 {
+            points.swap(1, 2);
+            colors.swap(1, 2);
+            area = -area;
+        }
+
+        let min_x = points.iter().map(|p| p[0]).fold(f32::INFINITY, f32::min).max(0f32);
+        let min_y = points.iter().map(|p| p[1]).fold(f32::INFINITY, f32::min).max(0f32);
+        let max_x = points.iter().map(|p| p[0]).fold(f32::NEG_INFINITY, f32::max).min(self.image.width as f32);
+        let max_y = points.iter().map(|p| p[1]).fold(f32::NEG_INFINITY, f32::max).min(self.image.height as f32);
+
+        let edges = [(1, 2), (2, 0), (0, 1)];
+        for y in min_y.floor() as u32..max_y.ceil() as u32 {
+            for x in min_x.floor() as u32..max_x.ceil() as u32 {
+                let p = [x as f32 + 0.5, y as f32 + 0.5];
+
+                let mut weights = [0f32; 3];
+                let inside = edges.iter().zip(&mut weights).all(|(&(a, b), weight)| {
+                    *weight = edge(points[a], points[b], p);
+                    *weight > 0f32 || (*weight == 0f32 && is_top_left(points[a], points[b]))
+                } );
+
+                if !inside {
+                    continue;
+                }
+
+                let mut color = [0f32; 4];
+                for (vertex_color, weight) in colors.iter().zip(weights) {
+                    for (c, v) in color.iter_mut().zip(vertex_color) {
+                        *c += v * weight / area;
+                    }
+                }
+
+                let index = self.image.index(x, y);
+                let pixel = &mut self.image.pixels[index];
+                let alpha = if blend { color[3] } else { 1f32 };
+                for (d, s) in pixel.iter_mut().zip(color) {
+                    *d = s * alpha + *d * (1f32 - alpha);
+                }
+            }
+        }
+    }
+}
+
+// Twice the signed area of the triangle abp, positive when p is clockwise from ab on screen
+fn edge(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+// For triangles wound clockwise on screen, top edges run to the right and left edges run up
+fn is_top_left(a: [f32; 2], b: [f32; 2]) -> bool {
+    (a[1] == b[1] && b[0] > a[0]) || b[1] < a[1]
+}
+
+pub(super) fn to_srgb8(linear: f32) -> u8 {
+    let linear = linear.clamp(0f32, 1f32);
+    let srgb = match linear <= 0.0031308 {
+        true => linear * 12.92,
+        false => 1.055 * linear.powf(2.4f32.recip()) - 0.055
+    };
+
+    (srgb * 255f32).round() as u8
+}
@@ -0,0 +1,250 @@
+use std::{
+    collections::VecDeque,
+    time::Instant
+};
+
+use cgmath::{MetricSpace, Point2};
+use winit::{
+    dpi::PhysicalPosition,
+    event,
+    event::WindowEvent
+};
+
+use crate::sim::{
+    Sim,
+    galaxy::Galaxy,
+    planet::PlanetFeature,
+    ship::ShipGoal
+};
+
+use super::{
+    Focus,
+    View,
+    camera::Camera
+};
+
+/// Takes over the camera when running as a screensaver
+/// Tours raids and busy stations, easing slowly from one to the next,
+/// and gives way to the user as soon as they touch anything
+pub(super) struct Screensaver {
+    stop: Stop,
+    recent: VecDeque<Focus>,
+    recent_systems: VecDeque<usize>,
+    stops: usize,
+    arrived: Instant,
+    last_frame: Instant,
+    cursor_origin: Option<PhysicalPosition<f64>>
+}
+
+// Where the camera is headed, the whole system if there's no focus
+#[derive(Copy, Clone)]
+struct Stop {
+    focus: Option<Focus>,
+    zoom: f32
+}
+
+impl Stop {
+    const OVERVIEW: Self = Self { focus: None, zoom: 1.0 };
+}
+
+impl Screensaver {
+    // Seconds spent at each stop, including the trip there
+    const DWELL: f32 = 15.0;
+
+    // Seconds for the camera to cover most of the way to a stop
+    const EASE: f32 = 2.5;
+
+    // Stops before moving on to another system
+    const STOPS_PER_SYSTEM: usize = 4;
+
+    // Stops that aren't revisited straight away
+    const RECENT: usize = 3;
+
+    const RAID_ZOOM: f32 = 0.4;
+    const STATION_ZOOM: f32 = 0.6;
+
+    // Ships within this distance of a station count towards how busy it is
+    const STATION_RANGE: f32 = 0.3;
+
+    // Pixels the cursor can drift before it counts as the user coming back
+    const CURSOR_THRESHOLD: f64 = 16.0;
+
+    pub(super) fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            stop: Stop::OVERVIEW,
+            recent: VecDeque::new(),
+            recent_systems: VecDeque::new(),
+            stops: 0,
+            arrived: now,
+            last_frame: now,
+            cursor_origin: None
+        }
+    }
+
+    /// Whether the event should end the screensaver
+    /// That's any key, click or scroll, or moving the mouse more than a little
+    pub(super) fn wakes(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput { input: event::KeyboardInput {
+                state: event::ElementState::Pressed,
+                ..
+            }, .. } => true,
+            WindowEvent::MouseInput { state: event::ElementState::Pressed, .. } => true,
+            WindowEvent::MouseWheel { .. } => true,
+            WindowEvent::CursorMoved { position, .. } => {
+                // The first position is wherever the cursor was when the screensaver started
+                let origin = *self.cursor_origin.get_or_insert(*position);
+                let (dx, dy) = (position.x - origin.x, position.y - origin.y);
+                (dx * dx + dy * dy).sqrt() > Self::CURSOR_THRESHOLD
+            },
+            _ => false
+        }
+    }
+
+    /// Called every frame, before the mesh is built
+    /// Picks the next stop once the current one has been shown for long enough
+    pub(super) fn update(&mut self, galaxy: &Galaxy, view: &mut View, camera: &mut Camera) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_frame).as_secs_f32();
+        self.last_frame = now;
+
+        let mut index = view.selected();
+        let stop_pos = |sim: &Sim, stop: Stop| match stop.focus {
+            Some(focus) => super::focus_pos(sim, focus),
+            None => Some(Point2::new(0f32, 0f32))
+        };
+
+        // Move on early if a ship being watched is gone
+        let done = now.duration_since(self.arrived).as_secs_f32() > Self::DWELL
+            || *view != View::System(index)
+            || stop_pos(&galaxy.systems[index], self.stop).is_none();
+
+        if done {
+            self.stops += 1;
+            self.arrived = now;
+
+            if *view != View::System(index) || self.stops.is_multiple_of(Self::STOPS_PER_SYSTEM) {
+                // Cut straight to an overview of the next system
+                self.recent_systems.push_back(index);
+                if self.recent_systems.len() > Self::RECENT {
+                    self.recent_systems.pop_front();
+                }
+
+                index = busiest_system(galaxy, &self.recent_systems).unwrap_or(index);
+                *view = View::System(index);
+                self.recent.clear();
+                self.stop = Stop::OVERVIEW;
+
+                camera.pos = Point2::new(0f32, 0f32);
+                camera.zoom = Stop::OVERVIEW.zoom;
+            } else {
+                self.stop = self.next_stop(&galaxy.systems[index]);
+            }
+        }
+
+        let sim = &galaxy.systems[index];
+        let pos = match stop_pos(sim, self.stop) {
+            Some(pos) => pos,
+            None => return
+        };
+
+        // Ease towards the stop, independent of the frame rate
+        let scale = super::system_scale(sim);
+        let t = 1f32 - (-elapsed / Self::EASE).exp();
+        camera.pos.x += (pos.x * scale - camera.pos.x) * t;
+        camera.pos.y += (pos.y * scale - camera.pos.y) * t;
+        camera.zoom += (self.stop.zoom - camera.zoom) * t;
+    }
+
+    /// The most interesting place in the system that hasn't been shown recently
+    fn next_stop(&mut self, sim: &Sim) -> Stop {
+        let next = interests(sim)
+            .into_iter()
+            .filter(|(focus, ..)| !self.recent.contains(focus))
+            .max_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+
+        match next {
+            Some((focus, _, zoom)) => {
+                self.recent.push_back(focus);
+                if self.recent.len() > Self::RECENT {
+                    self.recent.pop_front();
+                }
+
+                Stop { focus: Some(focus), zoom }
+            },
+            None => {
+                // Nothing going on, so pull back for a while
+                self.recent.clear();
+                Stop::OVERVIEW
+            }
+        }
+    }
+}
+
+/// Places worth watching in the system, with how interesting each is and how close to zoom
+/// Raids are the most exciting, then stations with lots of traffic around them
+fn interests(sim: &Sim) -> Vec<(Focus, f32, f32)> {
+    let raids = sim.ships.iter()
+        .enumerate()
+        .filter(|&(ship_index, ship)| {
+            matches!(ship.goal, ShipGoal::Hunt { .. }) && sim.pirate_in_range(ship_index)
+        } )
+        .map(|(_, ship)| (Focus::Ship(ship.id), 10f32, Screensaver::RAID_ZOOM));
+
+    let stations = sim.system.iter()
+        .enumerate()
+        .filter(|(_, pl)| matches!(pl.feat, Some(PlanetFeature::Station { .. })))
+        .map(|(pl_index, pl)| {
+            let traffic = sim.ships.iter()
+                .filter(|ship| ship.pos.distance(pl.pos) < Screensaver::STATION_RANGE)
+                .count();
+
+            (Focus::Planet(pl_index), traffic as f32, Screensaver::STATION_ZOOM)
+        } )
+        .filter(|&(_, traffic, _)| traffic > 0f32);
+
+    raids.chain(stations).collect()
+}
+
+/// The system with the most going on, out of those that weren't toured recently
+/// The longest ago is let back in if that rules out every system
+fn busiest_system(galaxy: &Galaxy, recent: &VecDeque<usize>) -> Option<usize> {
+    let busiest = |skip: &[usize]| {
+        galaxy.systems.iter()
+            .enumerate()
+            .filter(|(index, _)| !skip.contains(index))
+            .map(|(index, sim)| (index, interests(sim).iter().map(|(_, score, _)| score).sum::<f32>()))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index)
+    };
+
+    let recent: Vec<_> = recent.iter().copied().collect();
+    (0..=recent.len()).find_map(|skipped| busiest(&recent[skipped..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::SimConfig;
+
+    #[test]
+    fn raid_stop_stays_on_its_ship() {
+        let mut sim = Sim::new(SimConfig::default().with_seed(1));
+        let raid = (0..5000).find_map(|_| {
+            sim.update();
+            interests(&sim).into_iter().find_map(|(focus, ..)| match focus {
+                Focus::Ship(id) => Some(id),
+                Focus::Planet(..) => None
+            } )
+        } ).expect("no raid happened");
+
+        let raider_index = sim.ships.iter().position(|ship| ship.id == raid).unwrap();
+        let raider = sim.ships[raider_index].clone();
+        assert!(raider_index > 0);
+
+        // Removing a ship shifts every later one down a place
+        sim.ships.remove(0);
+        assert_eq!(super::super::focus_pos(&sim, Focus::Ship(raid)), Some(raider.pos));
+    }
+}
@@ -7,23 +7,56 @@ var<uniform> camera: CameraUniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
-    @location(1) color: vec3<f32>
+    @location(1) color: vec3<f32>,
+    @location(2) alpha: f32
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec3<f32>
+    @location(0) color: vec3<f32>,
+    @location(1) alpha: f32
 };
 
 @vertex
 fn vs_main(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     out.color = model.color;
+    out.alpha = model.alpha;
     out.clip_position = camera.projection * vec4<f32>(model.position, 1.0);
     return out;
 }
 
+struct ShapeInput {
+    @location(0) position: vec2<f32>,
+    @location(1) shade: f32
+};
+
+struct InstanceInput {
+    @location(2) position: vec2<f32>,
+    @location(3) rotation: f32,
+    @location(4) scale: f32,
+    @location(5) color: vec3<f32>
+};
+
+// Places a shape's unit mesh for a single instance
+@vertex
+fn vs_instance(shape: ShapeInput, instance: InstanceInput) -> VertexOutput {
+    let c = cos(instance.rotation);
+    let s = sin(instance.rotation);
+    let rotated = vec2<f32>(
+        c * shape.position.x - s * shape.position.y,
+        s * shape.position.x + c * shape.position.y
+    );
+    let position = rotated * instance.scale + instance.position;
+
+    var out: VertexOutput;
+    out.color = instance.color * shape.shade;
+    out.alpha = 1.0;
+    out.clip_position = camera.projection * vec4<f32>(position, 0.0, 1.0);
+    return out;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color, 1.0);
+    return vec4<f32>(in.color, in.alpha);
 }
\ No newline at end of file
@@ -0,0 +1,161 @@
+use std::io::{self, Write};
+
+use crate::sim::{
+    Sim,
+    planet::{Planet, BodyKind}
+};
+
+use super::{
+    Theme,
+    inspector,
+    mesh::Mesh,
+    raster
+};
+
+pub struct SvgConfig {
+    orbits: bool,
+    labels: bool,
+    size: u32
+}
+
+impl Default for SvgConfig {
+    fn default() -> Self {
+        Self {
+            orbits: false,
+            labels: false,
+            size: 1024
+        }
+    }
+}
+
+impl SvgConfig {
+    /// Draws the orbit of every body other than asteroids
+    pub fn with_orbits(mut self, orbits: bool) -> Self {
+        self.orbits = orbits;
+        self
+    }
+
+    /// Names the feature of each body next to it, other than asteroids
+    pub fn with_labels(mut self, labels: bool) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Width and height of the square image, in pixels
+    pub fn with_size(mut self, size: u32) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+/// Writes the system as SVG, in sim coordinates with y flipped so that up stays up
+/// Bodies are circles and ships the same triangles as Mesh::from_ship,
+/// colored as on screen, in a fixed order so that scenes can be diffed as text
+pub fn write_svg(sim: &Sim, theme: &Theme, config: &SvgConfig, w: &mut impl Write) -> io::Result<()> {
+    let rad = sim.system_rad;
+    let stroke = rad * 0.002;
+
+    writeln!(w, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="{min} {min} {extent} {extent}">"#,
+        size = config.size,
+        min = -rad,
+        extent = rad * 2f32
+    )?;
+    writeln!(w, r#"<rect x="{min}" y="{min}" width="{extent}" height="{extent}" fill="black"/>"#,
+        min = -rad,
+        extent = rad * 2f32
+    )?;
+
+    let bodies = sim.system.iter()
+        .enumerate()
+        .filter(|(_, planet)| planet.kind != BodyKind::Barycenter);
+
+    if config.orbits {
+        writeln!(w, r#"<g fill="none" stroke="{}" stroke-width="{:.4}">"#, hex([0.25f32; 3]), stroke)?;
+        for (_, planet) in bodies.clone().filter(|(_, planet)| planet.kind != BodyKind::Asteroid) {
+            if let Some(orbit) = &planet.orbit {
+                // The parent sits at one focus, so the ellipse's centre is offset towards apoapsis
+                let parent = sim.system[orbit.parent_index].pos;
+                let (sin, cos) = orbit.peri.sin_cos();
+                let c = orbit.dist * orbit.ecc;
+                let (x, y) = (parent.x - cos * c, flip(parent.y - sin * c));
+                if orbit.ecc == 0f32 {
+                    writeln!(w, r#"<circle cx="{:.4}" cy="{:.4}" r="{:.4}"/>"#, x, y, orbit.dist)?;
+                    continue;
+                }
+
+                writeln!(w, r#"<ellipse cx="{:.4}" cy="{:.4}" rx="{:.4}" ry="{:.4}" transform="rotate({:.2} {:.4} {:.4})"/>"#,
+                    x,
+                    y,
+                    orbit.dist,
+                    orbit.dist * (1f32 - orbit.ecc.powi(2)).sqrt(),
+                    flip(orbit.peri.to_degrees()),
+                    x,
+                    y
+                )?;
+            }
+        }
+        writeln!(w, "</g>")?;
+    }
+
+    for (pl_index, planet) in bodies.clone() {
+        // Comets fade out as they leave the inner system, just like on screen
+        let brightness = sim.comet_brightness(pl_index);
+        if brightness == 0f32 {
+            continue;
+        }
+
+        let color = theme.planet_color(planet).map(|c| c * brightness);
+        writeln!(w, r#"<circle cx="{:.4}" cy="{:.4}" r="{:.4}" fill="{}"/>"#,
+            planet.pos.x,
+            flip(planet.pos.y),
+            planet.rad,
+            hex(color)
+        )?;
+    }
+
+    let ship_colors = theme.ship_colors(sim.jobs());
+    for (ship_index, ship) in sim.ships.iter().enumerate() {
+        let points = Mesh::ship_points(ship)
+            .map(|p| format!("{:.4},{:.4}", p.x, flip(p.y)))
+            .join(" ");
+
+        writeln!(w, r#"<polygon points="{}" fill="{}"/>"#, points, hex(ship_colors.get(sim, ship_index)))?;
+    }
+
+    if config.labels {
+        writeln!(w, r#"<g fill="white" font-family="monospace" font-size="{:.4}">"#, rad * 0.025)?;
+        for (_, planet) in bodies.filter(|(_, planet)| planet.kind != BodyKind::Asteroid) {
+            if let Some(label) = label(planet) {
+                writeln!(w, r#"<text x="{:.4}" y="{:.4}">{}</text>"#,
+                    planet.pos.x + planet.rad * 1.5,
+                    flip(planet.pos.y),
+                    label
+                )?;
+            }
+        }
+        writeln!(w, "</g>")?;
+    }
+
+    writeln!(w, "</svg>")
+}
+
+// Negates y, or angles, to go from the sim's y up to SVG's y down
+// Subtracting from zero keeps zero positive, rather than printing as -0.0000
+fn flip(y: f32) -> f32 {
+    0f32 - y
+}
+
+// Suns and bodies with a feature are labelled, barren planets aren't
+fn label(planet: &Planet) -> Option<String> {
+    match (planet.kind, planet.feat) {
+        (BodyKind::Star, _) => Some(String::from("sun")),
+        (_, Some(..)) => Some(inspector::feature(planet)),
+        (_, None) => None
+    }
+}
+
+// Colors are linear, like the GPU's, so they're encoded as sRGB to match the screen
+pub(super) fn hex(color: [f32; 3]) -> String {
+    let [r, g, b] = color.map(raster::to_srgb8);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
@@ -0,0 +1,275 @@
+use wgpu::util::DeviceExt;
+
+use super::{
+    font,
+    buffer::DynamicBuffer
+};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct TextVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+    color: [f32; 4]
+}
+
+impl TextVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = {
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4]
+    };
+
+    fn description<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// Panels of text laid out in screen pixels, drawn on top of everything else
+/// Unlike Mesh, it doesn't move with the camera
+pub(super) struct TextMesh {
+    size: winit::dpi::PhysicalSize<u32>,
+    vertices: Vec<TextVertex>,
+    indices: Vec<u32>
+}
+
+impl TextMesh {
+    // Each font pixel covers SCALE x SCALE screen pixels
+    const SCALE: f32 = 2.0;
+
+    // Blank font pixels between the text and the edge of its panel
+    const MARGIN: f32 = 4.0;
+
+    const BACKGROUND: [f32; 4] = [0.05, 0.05, 0.05, 0.75];
+
+    pub(super) fn new(size: winit::dpi::PhysicalSize<u32>) -> Self {
+        Self {
+            size,
+            vertices: Vec::new(),
+            indices: Vec::new()
+        }
+    }
+
+    /// Width and height of the panel that would hold the text, in screen pixels
+    pub(super) fn panel_size(text: &str) -> (f32, f32) {
+        let columns = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let rows = text.lines().count();
+        (
+            ((columns * font::ADVANCE) as f32 + Self::MARGIN * 2f32) * Self::SCALE,
+            ((rows * font::LINE_HEIGHT) as f32 + Self::MARGIN * 2f32) * Self::SCALE
+        )
+    }
+
+    /// A translucent panel with its top left corner at (x, y), filled with text
+    pub(super) fn panel(&mut self, x: f32, y: f32, text: &str, color: [f32; 3]) {
+        let (width, height) = Self::panel_size(text);
+        self.quad([x, y, x + width, y + height], font::solid_uv(), Self::BACKGROUND);
+
+        let color = [color[0], color[1], color[2], 1f32];
+        let x = x + Self::MARGIN * Self::SCALE;
+        let y = y + Self::MARGIN * Self::SCALE;
+        for (row, line) in text.lines().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                if c == ' ' {
+                    continue;
+                }
+
+                let left = x + (column * font::ADVANCE) as f32 * Self::SCALE;
+                let top = y + (row * font::LINE_HEIGHT) as f32 * Self::SCALE;
+                self.quad([
+                    left,
+                    top,
+                    left + font::GLYPH_WIDTH as f32 * Self::SCALE,
+                    top + font::GLYPH_HEIGHT as f32 * Self::SCALE
+                ], font::uv(c), color);
+            }
+        }
+    }
+
+    // Bounds are [left, top, right, bottom] in screen pixels
+    fn quad(&mut self, bounds: [f32; 4], uv: [f32; 4], color: [f32; 4]) {
+        let to_clip = |x: f32, y: f32| [
+            x / self.size.width as f32 * 2f32 - 1f32,
+            1f32 - y / self.size.height as f32 * 2f32
+        ];
+
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend([
+            TextVertex { position: to_clip(bounds[0], bounds[1]), uv: [uv[0], uv[1]], color },
+            TextVertex { position: to_clip(bounds[2], bounds[1]), uv: [uv[2], uv[1]], color },
+            TextVertex { position: to_clip(bounds[2], bounds[3]), uv: [uv[2], uv[3]], color },
+            TextVertex { position: to_clip(bounds[0], bounds[3]), uv: [uv[0], uv[3]], color }
+        ] );
+        self.indices.extend([0, 1, 2, 2, 3, 0].map(|i| i + offset));
+    }
+}
+
+/// Second pipeline, which draws TextMesh with the font atlas
+/// Alpha blended so panels don't completely hide what's behind them
+pub(super) struct TextRenderer {
+    pipeline: wgpu::RenderPipeline,
+    atlas_bind_group: wgpu::BindGroup,
+    vertex_buffer: DynamicBuffer,
+    index_buffer: DynamicBuffer,
+    index_count: u32
+}
+
+impl TextRenderer {
+    pub(super) fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat, samples: u32) -> Self {
+        let atlas = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: font::ATLAS_WIDTH as u32,
+                    height: font::ATLAS_HEIGHT as u32,
+                    depth_or_array_layers: 1
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST
+            },
+            &font::atlas()
+        );
+
+        let atlas_view = atlas.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Glyphs are packed edge to edge, so filtering would bleed between them
+        let atlas_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        } );
+
+        let atlas_bind_group_layout = {
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true }
+                        },
+                        count: None
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None
+                    }
+                ],
+                label: None
+            }
+        ) };
+
+        let atlas_bind_group = {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &atlas_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&atlas_view)
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&atlas_sampler)
+                    }
+                ],
+                label: None
+            }
+        ) };
+
+        let shader = device.create_shader_module(
+            wgpu::include_wgsl!("text.wgsl")
+        );
+
+        let pipeline_layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[
+                    &atlas_bind_group_layout
+                ],
+                push_constant_ranges: &[]
+            }
+        );
+
+        let pipeline = device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[
+                        TextVertex::description()
+                    ]
+                },
+                fragment: Some(
+                    wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: "fs_main",
+                        targets: &[
+                            Some(wgpu::ColorTargetState {
+                                format,
+                                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                                write_mask: wgpu::ColorWrites::ALL
+                            } )
+                        ],
+                    }
+                ),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Cw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: samples,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None
+            }
+        );
+
+        Self {
+            pipeline,
+            atlas_bind_group,
+            vertex_buffer: DynamicBuffer::new(device, wgpu::BufferUsages::VERTEX),
+            index_buffer: DynamicBuffer::new(device, wgpu::BufferUsages::INDEX),
+            index_count: 0
+        }
+    }
+
+    pub(super) fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, text: &TextMesh) {
+        self.index_count = text.indices.len() as u32;
+        self.vertex_buffer.write(device, queue, bytemuck::cast_slice(&text.vertices));
+        self.index_buffer.write(device, queue, bytemuck::cast_slice(&text.indices));
+    }
+
+    /// Draws over whatever the render pass has drawn so far
+    pub(super) fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.index_count == 0 {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.atlas_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice());
+        render_pass.set_index_buffer(self.index_buffer.slice(), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    path::Path,
+    fs
+};
+
+use rand::Rng;
+use rand_seeder::SipHasher;
+
+use crate::sim::{
+    Sim,
+    ship::ShipGoal,
+    behavior::ShipRegistry,
+    planet::{Planet, PlanetFeature, BodyKind}
+};
+
+/// Colors of bodies and ships
+///
+/// Bodies are colored by kind, and planets by their feature:
+/// `sun`, `station`, `ore`, `ice`, `gate`, `barren`, `asteroid` and `comet`
+///
+/// Ships are colored by job, optionally per goal, like `trader` or `pirate.hunt`
+/// Jobs without a color fall back to their behavior's own
+#[derive(Clone)]
+pub struct Theme {
+    // Legacy coloring, where each sun and planet gets a random color
+    hashed_planets: bool,
+    colors: HashMap<String, [f32; 3]>
+}
+
+/// Ship colors of a theme, looked up for the jobs of one sim by Theme::ship_colors
+/// Indexed by JobId, so coloring a ship doesn't build any keys
+pub(super) struct ShipColors {
+    jobs: Vec<JobColors>
+}
+
+struct JobColors {
+    job: Option<[f32; 3]>,
+    // Indexed like Theme::GOALS
+    goals: [Option<[f32; 3]>; Theme::GOALS.len()]
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(false, &[
+            ("sun", 0xFFD34D),
+            ("station", 0x4DA6FF),
+            ("ore", 0xB5651D),
+            ("ice", 0xBFEFFF),
+            ("gate", 0xC77DFF),
+            ("barren", 0x6E6E6E),
+            ("asteroid", 0x8C8073),
+            ("comet", 0xB3E6FF)
+        ] )
+    }
+}
+
+impl Theme {
+    const GOALS: [&'static str; 5] = ["visit", "wait", "wander", "hunt", "scan"];
+
+    fn new(hashed_planets: bool, colors: &[(&str, u32)]) -> Self {
+        Self {
+            hashed_planets,
+            colors: colors.iter()
+                .map(|&(key, rgb)| (key.to_owned(), from_rgb(rgb)))
+                .collect()
+        }
+    }
+
+    /// Every sun and planet gets its own random color, as in older versions
+    pub fn hashed() -> Self {
+        Self::new(true, &[
+            ("asteroid", 0x8C8073),
+            ("comet", 0xB3E6FF)
+        ] )
+    }
+
+    /// Okabe and Ito's palette, distinguishable with any common color blindness
+    pub fn okabe_ito() -> Self {
+        Self::new(false, &[
+            ("sun", 0xF0E442),
+            ("station", 0x0072B2),
+            ("ore", 0xE69F00),
+            ("ice", 0xFFFFFF),
+            ("gate", 0xCC79A7),
+            ("barren", 0x999999),
+            ("asteroid", 0x7F7F7F),
+            ("comet", 0xDDDDDD),
+            ("trader", 0x009E73),
+            ("miner", 0x56B4E9),
+            ("pirate", 0xD55E00)
+        ] )
+    }
+
+    /// Paul Tol's bright scheme, also safe for color blindness
+    pub fn tol() -> Self {
+        Self::new(false, &[
+            ("sun", 0xCCBB44),
+            ("station", 0x4477AA),
+            ("ore", 0xAA3377),
+            ("ice", 0x66CCEE),
+            ("gate", 0x228833),
+            ("barren", 0xBBBBBB),
+            ("asteroid", 0x888888),
+            ("comet", 0xFFFFFF),
+            ("trader", 0x228833),
+            ("miner", 0x66CCEE),
+            ("pirate", 0xEE6677)
+        ] )
+    }
+
+    /// One of the themes above, by name
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "hashed" => Some(Self::hashed()),
+            "okabe-ito" => Some(Self::okabe_ito()),
+            "tol" => Some(Self::tol()),
+            _ => None
+        }
+    }
+
+    /// Reads a theme file, with a `key = #rrggbb` pair on each line
+    /// `planets = hashed` switches to random planet colors
+    /// Anything left out keeps its default color, bad lines are reported and skipped
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let mut theme = Self::default();
+
+        let source = fs::read_to_string(path)?;
+        for (line_index, line) in source.lines().enumerate() {
+            // Lines starting with a hash are comments
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Err(e) = theme.apply(line) {
+                eprintln!("{}:{}: {}", path.display(), line_index + 1, e);
+            }
+        }
+
+        Ok(theme)
+    }
+
+    fn apply(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line.split_once('=')
+            .ok_or_else(|| String::from("expected `key = value`"))?;
+        let (key, value) = (key.trim(), value.trim());
+
+        if key == "planets" {
+            self.hashed_planets = match value {
+                "hashed" => true,
+                "features" => false,
+                _ => return Err(format!("unknown planet coloring `{}`", value))
+            };
+
+            return Ok(());
+        }
+
+        // Job names can't be checked, since jobs are registered at runtime
+        let valid = match key.split_once('.') {
+            Some((_, goal)) => Self::GOALS.contains(&goal),
+            None => true
+        };
+
+        if !valid {
+            return Err(format!("unknown goal in `{}`", key));
+        }
+
+        let rgb = value.strip_prefix('#')
+            .filter(|hex| hex.len() == 6)
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| format!("expected a color like #ff8800, found `{}`", value))?;
+
+        self.colors.insert(key.to_owned(), from_rgb(rgb));
+
+        Ok(())
+    }
+
+    /// Looks up the colors of every job in the registry,
+    /// once for each mesh rather than once for each ship
+    pub(super) fn ship_colors(&self, jobs: &ShipRegistry) -> ShipColors {
+        ShipColors {
+            jobs: jobs.jobs().map(|job| {
+                let name = jobs.get(job).name();
+                JobColors {
+                    job: self.colors.get(name).copied(),
+                    goals: Self::GOALS.map(|goal| {
+                        self.colors.get(&format!("{}.{}", name, goal)).copied()
+                    } )
+                }
+            } ).collect()
+        }
+    }
+
+    fn color(&self, key: &str) -> [f32; 3] {
+        self.colors.get(key).copied().unwrap_or([1f32; 3])
+    }
+
+    pub(super) fn planet_color(&self, planet: &Planet) -> [f32; 3] {
+        match planet.kind {
+            BodyKind::Asteroid => return self.color("asteroid"),
+            BodyKind::Comet => return self.color("comet"),
+            _ if self.hashed_planets => return hashed_color(planet),
+            BodyKind::Star => return self.color("sun"),
+            _ => {  }
+        }
+
+        self.color(match planet.feat {
+            Some(PlanetFeature::Station { .. }) => "station",
+            Some(PlanetFeature::Ore { .. }) => "ore",
+            Some(PlanetFeature::Ice) => "ice",
+            Some(PlanetFeature::Gate { .. }) => "gate",
+            None => "barren"
+        } )
+    }
+
+}
+
+impl ShipColors {
+    /// The color for the ship's job and goal, or failing that its job
+    pub(super) fn get(&self, sim: &Sim, ship_index: usize) -> [f32; 3] {
+        let ship = &sim.ships[ship_index];
+
+        self.jobs.get(ship.job.0)
+            .and_then(|colors| colors.goals[goal_index(ship.goal)].or(colors.job))
+            .unwrap_or_else(|| sim.behavior(ship_index).color(ship))
+    }
+}
+
+// Position of the goal in Theme::GOALS
+fn goal_index(goal: ShipGoal) -> usize {
+    Theme::GOALS.iter().position(|&name| name == goal.name()).unwrap()
+}
+
+// Colors are written as sRGB, but everything downstream blends in linear space
+fn from_rgb(rgb: u32) -> [f32; 3] {
+    [rgb >> 16, rgb >> 8, rgb].map(|channel| from_srgb8((channel & 0xFF) as u8))
+}
+
+// Inverse of raster::to_srgb8
+fn from_srgb8(srgb: u8) -> f32 {
+    let srgb = srgb as f32 / 255f32;
+    match srgb <= 0.04045 {
+        true => srgb / 12.92,
+        false => ((srgb + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Hashed from the planet's size and orbit, so it never changes
+fn hashed_color(planet: &Planet) -> [f32; 3] {
+    let mut h = SipHasher::new();
+    planet.rad.to_string().hash(&mut h);
+    if let Some(o) = &planet.orbit {
+        o.dist.to_string().hash(&mut h);
+        o.speed.to_string().hash(&mut h);
+        o.ccw.hash(&mut h);
+    }
+
+    let mut h = h.into_rng();
+
+    [
+        h.gen_range(0f32..1f32),
+        h.gen_range(0f32..1f32),
+        h.gen_range(0f32..1f32)
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::{svg, raster};
+
+    #[test]
+    fn palette_round_trips_through_svg() {
+        assert_eq!(svg::hex(Theme::okabe_ito().color("station")), "#0072b2");
+
+        for rgb in [0x000000, 0xFFFFFF, 0xE69F00, 0x56B4E9, 0x009E73, 0xD55E00] {
+            assert_eq!(svg::hex(from_rgb(rgb)), format!("#{:06x}", rgb));
+        }
+    }
+
+    #[test]
+    fn ships_take_their_job_color() {
+        let sim = Sim::new(crate::sim::SimConfig::default().with_seed(1));
+        let miner = (0..sim.ships.len())
+            .find(|&ship_index| sim.behavior(ship_index).name() == "miner")
+            .unwrap();
+
+        let ship_colors = Theme::okabe_ito().ship_colors(sim.jobs());
+        assert_eq!(svg::hex(ship_colors.get(&sim, miner)), "#56b4e9");
+    }
+
+    #[test]
+    fn every_channel_value_round_trips() {
+        for srgb in 0..=255u8 {
+            assert_eq!(raster::to_srgb8(from_srgb8(srgb)), srgb);
+        }
+    }
+}
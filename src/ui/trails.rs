@@ -0,0 +1,108 @@
+use std::collections::{HashMap, VecDeque};
+
+use cgmath::Point2;
+
+use crate::sim::{
+    Sim,
+    galaxy::Galaxy
+};
+
+use super::{
+    mesh::Mesh,
+    theme::Theme
+};
+
+/// Recent positions of every ship in the galaxy, keyed by ship id
+/// Positions are only kept every few ticks, the gaps are too small to notice
+pub(super) struct Trails {
+    paths: HashMap<u64, Path>,
+    tick: usize
+}
+
+struct Path {
+    system: usize,
+    points: VecDeque<Point2<f32>>,
+    last_seen: usize
+}
+
+impl Trails {
+    // Ticks between recorded positions
+    const SPACING: usize = 4;
+
+    pub(super) fn new() -> Self {
+        Self {
+            paths: HashMap::new(),
+            tick: 0
+        }
+    }
+
+    /// Called with every tick
+    /// Trails of ships that are gone are dropped along with them
+    pub(super) fn record(&mut self, galaxy: &Galaxy) {
+        self.tick += 1;
+        if !self.tick.is_multiple_of(Self::SPACING) {
+            return;
+        }
+
+        for (system, sim) in galaxy.systems.iter().enumerate() {
+            for (ship_index, ship) in sim.ships.iter().enumerate() {
+                let length = sim.behavior(ship_index).trail().length / Self::SPACING;
+                if length == 0 {
+                    continue;
+                }
+
+                let path = self.paths.entry(ship.id).or_insert(Path {
+                    system,
+                    points: VecDeque::new(),
+                    last_seen: self.tick
+                } );
+
+                // Don't draw a line across the jump between systems
+                if path.system != system {
+                    path.system = system;
+                    path.points.clear();
+                }
+
+                path.points.push_back(ship.pos);
+                while path.points.len() > length {
+                    path.points.pop_front();
+                }
+
+                path.last_seen = self.tick;
+            }
+        }
+
+        let tick = self.tick;
+        self.paths.retain(|_, path| path.last_seen == tick);
+    }
+
+    /// Forgets every trail, after a rewind they would lead to where ships used to be
+    pub(super) fn clear(&mut self) {
+        self.paths.clear();
+    }
+
+    /// Trails of every ship in the given system, in sim coordinates
+    /// Each ends at the ship's current position, between recorded points
+    pub(super) fn build_mesh(&self, sim: &Sim, system: usize, width: f32, theme: &Theme) -> Mesh {
+        let mut m = Mesh::default();
+        let ship_colors = theme.ship_colors(sim.jobs());
+        for (ship_index, ship) in sim.ships.iter().enumerate() {
+            let path = match self.paths.get(&ship.id) {
+                Some(path) if path.system == system => path,
+                _ => continue
+            };
+
+            let opacity = sim.behavior(ship_index).trail().opacity;
+            let mut points: Vec<_> = path.points.iter().copied().collect();
+            points.push(ship.pos);
+
+            super::combine_meshes(
+                &mut m,
+                Mesh::trail(&points, width, ship_colors.get(sim, ship_index), opacity),
+                1f32
+            );
+        }
+
+        m
+    }
+}
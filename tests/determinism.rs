@@ -0,0 +1,32 @@
+use solarsim::sim::galaxy::{Galaxy, GalaxyConfig};
+
+// Long enough for stations to launch ships and pirates to raid them
+const TICKS: usize = 300;
+
+fn run_on(threads: usize) -> Galaxy {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+
+    pool.install(|| {
+        let mut galaxy = Galaxy::new(GalaxyConfig::default().with_seed(7));
+        for _ in 0..TICKS {
+            galaxy.update();
+        }
+
+        galaxy
+    } )
+}
+
+#[test]
+fn thread_count_does_not_change_the_outcome() {
+    let serial = run_on(1);
+    let parallel = run_on(4);
+
+    assert_eq!(serial.systems.len(), parallel.systems.len());
+    for (a, b) in serial.systems.iter().zip(&parallel.systems) {
+        assert_eq!(a.system, b.system);
+        assert_eq!(a.ships, b.ships);
+    }
+}
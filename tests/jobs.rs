@@ -0,0 +1,77 @@
+use rand::rngs::SmallRng;
+
+use solarsim::sim::{
+    Sim,
+    SimConfig,
+    behavior::ShipBehavior,
+    planet::PlanetFeature,
+    ship::{Ship, ShipGoal, ShipIntent, JobId}
+};
+
+// Shuttles between stations, avoiding danger like a trader,
+// using only what's public to jobs outside the crate
+struct Courier;
+
+impl ShipBehavior for Courier {
+    fn name(&self) -> &'static str { "courier" }
+
+    fn populate(&self, sim: &mut Sim, job: JobId) {
+        let stations = sim.station_indices();
+        let mut ship = Ship::new(job, sim.config.ship_speed(), &mut sim.prng);
+        ship.pos = sim.system[stations[0]].pos;
+        ship.goal = ShipGoal::Visit { target: *stations.last().unwrap() };
+        sim.ships.push(ship);
+    }
+
+    fn update(&self, sim: &Sim, ship_index: usize, rng: &mut SmallRng) -> ShipIntent {
+        match sim.ships[ship_index].goal {
+            ShipGoal::Visit { target } => {
+                let mut ship = sim.ships[ship_index].clone();
+                let dest_pos = sim.danger.detour(
+                    ship.pos,
+                    sim.system[target].pos,
+                    sim.config.danger_rad());
+
+                let complete = sim.approach(&mut ship, target, dest_pos);
+                ShipIntent::new(ship, complete)
+            },
+            _ => sim.follow_goal(ship_index, rng)
+        }
+    }
+
+    fn next_goal(&self, sim: &mut Sim, ship_index: usize) -> ShipGoal {
+        // The farthest station from here
+        let stations = sim.nearest_with_feature(
+            Some(PlanetFeature::Station { stock: 0 }),
+            sim.ships[ship_index].pos);
+        ShipGoal::Visit { target: *stations.last().unwrap() }
+    }
+
+    fn allows_goal(&self, sim: &Sim, _ship_index: usize, goal: ShipGoal) -> bool {
+        match goal {
+            ShipGoal::Visit { target } => sim.station_indices().contains(&target),
+            _ => false
+        }
+    }
+
+    fn color(&self, _ship: &Ship) -> [f32; 3] { [1f32; 3] }
+}
+
+#[test]
+fn outside_job_runs() {
+    let mut config = SimConfig::default().with_seed(1);
+    let courier = config.register_job(Courier);
+
+    let mut sim = Sim::new(config);
+    let start = sim.ships.iter().find(|ship| ship.job == courier).unwrap().pos;
+    for _ in 0..100 {
+        sim.update();
+    }
+
+    if let Err(report) = sim.validate() {
+        panic!("{}", report);
+    }
+
+    let ship = sim.ships.iter().find(|ship| ship.job == courier).unwrap();
+    assert_ne!(ship.pos, start);
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "ui")]
+
+use std::{fs, path::Path};
+
+use solarsim::{
+    sim::galaxy::{Galaxy, GalaxyConfig},
+    ui
+};
+
+// Set to rewrite the golden images after an intended change to the output
+const BLESS: &str = "SOLARSIM_BLESS";
+
+// Same as `solarsim render-frame --seed 3 --tick 100 --size 96x64`
+fn render_frame(seed: u64, ticks: usize, width: u32, height: u32) -> ui::Image {
+    let mut galaxy = Galaxy::new(GalaxyConfig::default().with_seed(seed));
+    for _ in 0..ticks {
+        galaxy.update();
+    }
+
+    let sim = &galaxy.systems[0];
+    let theme = ui::Theme::default();
+    let camera = ui::Camera::new((0f32, 0f32).into(), 1f32, width as f32 / height as f32);
+    ui::rasterize(&ui::build_mesh(sim, &theme), &camera, width, height)
+}
+
+// Pixels are compared rather than files, so changes to the PNG encoder don't matter
+fn read_rgb8(path: &Path) -> (u32, u32, Vec<u8>) {
+    let decoder = png::Decoder::new(fs::File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    pixels.truncate(info.buffer_size());
+
+    (info.width, info.height, pixels)
+}
+
+#[test]
+fn frame_matches_golden_image() {
+    let image = render_frame(3, 100, 96, 64);
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/seed3-tick100.png");
+
+    if std::env::var_os(BLESS).is_some() {
+        image.write_png(fs::File::create(&path).unwrap()).unwrap();
+    }
+
+    let (width, height, pixels) = read_rgb8(&path);
+    assert_eq!((width, height), (image.width(), image.height()));
+    assert!(pixels == image.to_rgb8(), "frame differs from {}, rerun with {}=1 if that's intended", path.display(), BLESS);
+}
@@ -0,0 +1,168 @@
+use solarsim::sim::{
+    Sim,
+    SimConfig,
+    ship::ShipGoal,
+    planet::PlanetFeature,
+    validate::Violation
+};
+
+// Far past the end of any generated system
+const MISSING: usize = 100_000;
+
+fn sim() -> Sim {
+    Sim::new(SimConfig::default().with_seed(1))
+}
+
+// Bodies that orbit another, rather than being a root of the system
+fn orbiting(sim: &Sim) -> Vec<usize> {
+    (0..sim.system.len())
+        .filter(|&pl_index| sim.system[pl_index].orbit.is_some())
+        .collect()
+}
+
+fn ship_with_job(sim: &Sim, name: &str) -> usize {
+    (0..sim.ships.len())
+        .find(|&ship_index| sim.behavior(ship_index).name() == name)
+        .unwrap()
+}
+
+// Every violation found in a sim that's been deliberately broken
+fn violations(sim: &Sim) -> Vec<Violation> {
+    sim.validate().unwrap_err().violations
+}
+
+#[test]
+fn generated_sim_is_valid() {
+    let mut sim = sim();
+    if let Err(report) = sim.validate() {
+        panic!("{}", report);
+    }
+
+    for _ in 0..100 {
+        sim.update();
+    }
+    if let Err(report) = sim.validate() {
+        panic!("{}", report);
+    }
+}
+
+#[test]
+fn missing_parent() {
+    let mut sim = sim();
+    let pl_index = orbiting(&sim)[0];
+    sim.system[pl_index].orbit.as_mut().unwrap().parent_index = MISSING;
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::MissingParent { pl_index: p, parent_index: MISSING } if *p == pl_index)));
+}
+
+#[test]
+fn missing_moon() {
+    let mut sim = sim();
+    sim.system[0].moon_indices.push(MISSING);
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::MissingMoon { pl_index: 0, moon_index: MISSING })));
+}
+
+#[test]
+fn unlisted_moon() {
+    let mut sim = sim();
+    let moon = orbiting(&sim)[0];
+    let parent = sim.system[moon].orbit.unwrap().parent_index;
+    sim.system[parent].moon_indices.retain(|&m| m != moon);
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::UnlistedMoon { pl_index, parent_index } if *pl_index == moon && *parent_index == parent)));
+}
+
+#[test]
+fn moon_of_other() {
+    let mut sim = sim();
+    let moon = orbiting(&sim)[0];
+    let parent = sim.system[moon].orbit.unwrap().parent_index;
+    let other = (0..sim.system.len())
+        .find(|&pl_index| pl_index != parent && pl_index != moon)
+        .unwrap();
+    sim.system[other].moon_indices.push(moon);
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::MoonOfOther { pl_index, moon_index } if *pl_index == other && *moon_index == moon)));
+}
+
+#[test]
+fn orbit_cycle() {
+    let mut sim = sim();
+    let orbiting = orbiting(&sim);
+    let (a, b) = (orbiting[0], orbiting[1]);
+    sim.system[a].orbit.as_mut().unwrap().parent_index = b;
+    sim.system[b].orbit.as_mut().unwrap().parent_index = a;
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::OrbitCycle { pl_index } if *pl_index == a)));
+}
+
+#[test]
+fn missing_target() {
+    let mut sim = sim();
+    sim.ships[0].goal = ShipGoal::Visit { target: MISSING };
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::MissingTarget { ship_index: 0, target: MISSING })));
+}
+
+#[test]
+fn wrong_feature() {
+    let mut sim = sim();
+    let ore = (0..sim.system.len())
+        .find(|&pl_index| matches!(sim.system[pl_index].feat, Some(PlanetFeature::Ore { .. })))
+        .unwrap();
+
+    // Ore is worth a miner's visit, but traders only deal with stations and gates
+    let ship_index = ship_with_job(&sim, "miner");
+    sim.ships[ship_index].job = sim.jobs().find("trader").unwrap();
+    sim.ships[ship_index].goal = ShipGoal::Visit { target: ore };
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::WrongFeature { ship_index: s, target } if *s == ship_index && *target == ore)));
+}
+
+#[test]
+fn missing_prey() {
+    let mut sim = sim();
+    let pirate = ship_with_job(&sim, "pirate");
+    sim.ships[pirate].goal = ShipGoal::Hunt { prey: MISSING, progress: 0 };
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::MissingPrey { ship_index, prey: MISSING } if *ship_index == pirate)));
+}
+
+#[test]
+fn nan_planet() {
+    let mut sim = sim();
+    sim.system[0].pos.x = f32::NAN;
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::NanPlanet { pl_index: 0 })));
+}
+
+#[test]
+fn nan_ship() {
+    let mut sim = sim();
+    sim.ships[0].pos.y = f32::NAN;
+
+    assert!(violations(&sim).iter().any(|v| matches!(v,
+        Violation::NanShip { ship_index: 0 })));
+}
+
+#[test]
+fn no_station_or_ore() {
+    let mut sim = sim();
+    for pl in sim.system.iter_mut() {
+        pl.feat = None;
+    }
+
+    let violations = violations(&sim);
+    assert!(violations.iter().any(|v| matches!(v, Violation::NoStation)));
+    assert!(violations.iter().any(|v| matches!(v, Violation::NoOre)));
+}